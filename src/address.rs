@@ -0,0 +1,91 @@
+//! Deterministic addresses of the accounts of an event
+//!
+//! The event account is a program derived address of its creator and a
+//! creator-chosen `event_id`, so clients can find an event without an
+//! off-chain index. Its metadata, outcome mint, collateral vault, fee vault,
+//! resolution and authority accounts are program derived addresses of the
+//! event, and the program-wide config a program derived address of the
+//! program.
+//!
+//! `CreateEvent` allocates the accounts of an event itself, signing for their
+//! addresses, and funds them on top of any lamports already sent there, so
+//! lamports sent to an address beforehand don't keep its `event_id` from
+//! being used.
+
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+/// Seed prefix of the event authority
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+
+/// Seed of the program config
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed prefix of the event account
+pub const EVENT_SEED: &[u8] = b"event";
+
+/// Seed prefix of the metadata account
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+/// Seed prefix of the token mint of an outcome
+pub const MINT_SEED: &[u8] = b"mint";
+
+/// Seed prefix of the collateral vault
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Seed prefix of the fee vault
+pub const FEE_VAULT_SEED: &[u8] = b"fees";
+
+/// Seed prefix of the resolution account
+pub const RESOLUTION_SEED: &[u8] = b"resolution";
+
+/// Address and bump seed of the event account of `event_id` created by
+/// `creator`
+pub fn find_event_address(program_id: &Pubkey, creator: &Pubkey, event_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[EVENT_SEED, &creator.to_bytes(), &event_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Address and bump seed of the metadata account of an event
+pub fn find_metadata_address(program_id: &Pubkey, event: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[METADATA_SEED, &event.to_bytes()], program_id)
+}
+
+/// Address and bump seed of the token mint of an outcome of an event
+pub fn find_mint_address(program_id: &Pubkey, event: &Pubkey, outcome_index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_SEED, &event.to_bytes(), &[outcome_index]], program_id)
+}
+
+/// Address and bump seed of the collateral vault of an event
+pub fn find_vault_address(program_id: &Pubkey, event: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, &event.to_bytes()], program_id)
+}
+
+/// Address and bump seed of the fee vault of an event, owned by the program
+/// for lamport events or by the token program for SPL collateral events
+pub fn find_fee_vault_address(program_id: &Pubkey, event: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_VAULT_SEED, &event.to_bytes()], program_id)
+}
+
+/// Address and bump seed of the resolution account of an event
+pub fn find_resolution_address(program_id: &Pubkey, event: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RESOLUTION_SEED, &event.to_bytes()], program_id)
+}
+
+/// Address and bump seed of the authority of an event, mint authority of its
+/// outcome tokens and owner of its collateral vault
+pub fn find_authority_address(program_id: &Pubkey, event: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED, &event.to_bytes()], program_id)
+}
+
+/// Address and bump seed of the program config
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// Address of the program data account of the program, holding its upgrade
+/// authority
+pub fn find_program_data_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id())
+}
@@ -2,7 +2,7 @@
 
 #![cfg(all(target_arch = "bpf", not(feature = "no-entrypoint")))]
 
-use crate::{error::PoolError, processor};
+use crate::{error::PredictChainError, processor};
 use solana_program::{
     account_info::AccountInfo, entrypoint, entrypoint::ProgramResult,
     program_error::PrintProgramError, pubkey::Pubkey,
@@ -18,7 +18,7 @@ fn process_instruction(
         processor::Processor::process_instruction(program_id, accounts, instruction_data)
     {
         // catch the error so we can print it
-        // error.print::<PoolError>();
+        error.print::<PredictChainError>();
         return Err(error);
     }
     Ok(())
@@ -10,52 +10,162 @@ use thiserror::Error;
 /// Errors that may be returned by the PredictChain program.
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum PredictChainError {
-    /// Generic error for now
-    #[error("PredictChain generic error")]
-    GenericError,
-
-    // /// PredictChain account already in use
-    // #[error("PredictChain account already in use")]
-    // AlreadyInUse,
-    // /// Deposit account already in use
-    // #[error("Deposit account already in use")]
-    // DepositAccountInUse,
-    // /// Token mint account already in use
-    // #[error("Token account already in use")]
-    // TokenMintInUse,
-    // /// Invalid seed or bump_seed was provided
-    // #[error("Failed to generate program account because of invalid data")]
-    // InvalidAuthorityData,
-    // /// Invalid authority account provided
-    // #[error("Invalid authority account provided")]
-    // InvalidAuthorityAccount,
-    // /// Lamport balance below rent-exempt threshold.
-    // #[error("Lamport balance below rent-exempt threshold")]
-    // NotRentExempt,
-    // /// Expected an SPL Token mint
-    // #[error("Input token mint account is not valid")]
-    // InvalidTokenMint,
-    // /// Amount should be more than zero
-    // #[error("Amount should be more than zero")]
-    // InvalidAmount,
-    // /// Wrong decider account
-    // #[error("Wrong decider account was sent")]
-    // WrongDeciderAccount,
-    // /// Signature missing in transaction
-    // #[error("Signature missing in transaction")]
-    // SignatureMissing,
-    // /// Decision was already made for this PredictChain
-    // #[error("Decision was already made for this PredictChain")]
-    // DecisionAlreadyMade,
-    // /// Decision can't be made in current slot
-    // #[error("Decision can't be made in current slot")]
-    // InvalidSlotForDecision,
-    // /// Deposit can't be made in current slot
-    // #[error("Deposit can't be made in current slot")]
-    // InvalidSlotForDeposit,
-    // /// No decision has been made yet
-    // #[error("No decision has been made yet")]
-    // NoDecisionMadeYet,
+    /// PredictChain account already in use
+    #[error("PredictChain account already in use")]
+    AlreadyInUse,
+    /// Invalid seed or bump_seed was provided
+    #[error("Failed to generate program account because of invalid data")]
+    InvalidAuthorityData,
+    /// Invalid authority account provided
+    #[error("Invalid authority account provided")]
+    InvalidAuthorityAccount,
+    /// Lamport balance below rent-exempt threshold.
+    #[error("Lamport balance below rent-exempt threshold")]
+    NotRentExempt,
+    /// Expected an SPL Token mint of the event
+    #[error("Input token mint account is not valid")]
+    InvalidTokenMint,
+    /// Amount should be more than zero
+    #[error("Amount should be more than zero")]
+    InvalidAmount,
+    /// Wrong resolve authority account
+    #[error("Wrong resolve authority account was sent")]
+    WrongResolveAuthority,
+    /// Signature missing in transaction
+    #[error("Signature missing in transaction")]
+    SignatureMissing,
+    /// Decision was already made for this PredictChain
+    #[error("Decision was already made for this PredictChain")]
+    DecisionAlreadyMade,
+    /// No decision has been made yet
+    #[error("No decision has been made yet")]
+    NoDecisionMadeYet,
+    /// Side index is not an outcome of the event
+    #[error("Side index is not an outcome of the event")]
+    InvalidSide,
+    /// Account is owned by the wrong program or user
+    #[error("Account is owned by the wrong program or user")]
+    WrongOwner,
+    /// Event is no longer open
+    #[error("Event is no longer open")]
+    EventClosed,
+    /// Shares can't be traded outside the event trading window
+    #[error("Trading is outside the event window")]
+    OutsideTradingWindow,
+    /// Price moved beyond the accepted cost or proceeds
+    #[error("Price moved beyond the accepted cost or proceeds")]
+    SlippageExceeded,
+    /// Invalid number or labels of outcomes
+    #[error("Invalid number or labels of outcomes")]
+    InvalidOutcomes,
+    /// Scalar bounds or trading window are empty
+    #[error("Invalid scalar bounds or trading window")]
+    InvalidBounds,
+    /// Instruction does not apply to the event type
+    #[error("Instruction does not apply to the event type")]
+    WrongEventType,
+    /// Instruction does not apply to the event pricing mode
+    #[error("Instruction does not apply to the event pricing mode")]
+    InvalidPricingMode,
+    /// Account is not at the address expected by the event
+    #[error("Account is not at the address expected by the event")]
+    WrongAccountAddress,
+    /// Collateral vault or token account missing or invalid
+    #[error("Collateral vault or token account missing or invalid")]
+    InvalidCollateralAccount,
+    /// Expected the SPL Token program
+    #[error("Token program account is not the SPL Token program")]
+    InvalidTokenProgram,
+    /// Expected the System program
+    #[error("System program account is not the System program")]
+    InvalidSystemProgram,
+    /// Event pool is too small for the payout
+    #[error("Event pool is too small for the payout")]
+    InsufficientFunds,
+    /// Outcome has nothing to pay out
+    #[error("Outcome has nothing to pay out")]
+    NotRedeemable,
+    /// Metadata string longer than its maximum length
+    #[error("Metadata string longer than its maximum length")]
+    InvalidMetadata,
+    /// Metadata can't be edited once trading opens
+    #[error("Metadata can't be edited once trading opens")]
+    MetadataLocked,
+    /// Signer is not the event creator
+    #[error("Signer is not the event creator")]
+    WrongCreator,
+    /// Market maker reserves can't cover the trade
+    #[error("Market maker reserves can't cover the trade")]
+    InsufficientLiquidity,
+    /// Arithmetic overflow
+    #[error("Arithmetic overflow")]
+    MathOverflow,
+    /// Event has not been voided
+    #[error("Event has not been voided")]
+    EventNotVoided,
+    /// Event account is not initialized
+    #[error("Event account is not initialized")]
+    UninitializedAccount,
+    /// Account data length does not match the event
+    #[error("Account data length does not match the event")]
+    InvalidAccountSize,
+    /// Fee rates exceed the maximum
+    #[error("Fee rates exceed the maximum")]
+    InvalidFee,
+    /// Signer is not a fee recipient of the event
+    #[error("Signer is not a fee recipient of the event")]
+    WrongFeeRecipient,
+    /// Program is paused
+    #[error("Program is paused")]
+    ProgramPaused,
+    /// Signer is not the config admin
+    #[error("Signer is not the config admin")]
+    WrongAdmin,
+    /// No resolve authority is pending for the event
+    #[error("No resolve authority is pending for the event")]
+    NoPendingResolveAuthority,
+    /// Resolver set or threshold is invalid
+    #[error("Resolver set or threshold is invalid")]
+    InvalidResolvers,
+    /// Signer is not a resolver of the event
+    #[error("Signer is not a resolver of the event")]
+    WrongResolver,
+    /// Event is resolved by votes of its resolver set
+    #[error("Event is resolved by votes of its resolver set")]
+    ResolverVoteRequired,
+    /// Event does not take this proposal
+    #[error("Event does not take this proposal")]
+    InvalidProposal,
+    /// Event is resolved by bonded proposals
+    #[error("Event is resolved by bonded proposals")]
+    ProposalRequired,
+    /// Proposal status does not allow the instruction
+    #[error("Proposal status does not allow the instruction")]
+    WrongProposalStatus,
+    /// Dispute window has ended
+    #[error("Dispute window has ended")]
+    DisputeWindowClosed,
+    /// Dispute window has not ended
+    #[error("Dispute window has not ended")]
+    DisputeWindowOpen,
+    /// Oracle settings or price account are invalid
+    #[error("Oracle settings or price account are invalid")]
+    InvalidOracle,
+    /// Event is resolved from its oracle
+    #[error("Event is resolved from its oracle")]
+    OracleRequired,
+    /// Event trading window has not closed
+    #[error("Event trading window has not closed")]
+    TradingNotClosed,
+    /// Oracle price is stale
+    #[error("Oracle price is stale")]
+    StalePrice,
+    /// Oracle price is not trading or its confidence is too wide
+    #[error("Oracle price is not trading or its confidence is too wide")]
+    UncertainPrice,
+    /// Signer is not the upgrade authority of the program
+    #[error("Signer is not the upgrade authority of the program")]
+    WrongUpgradeAuthority,
 }
 
 impl From<PredictChainError> for ProgramError {
@@ -76,27 +186,118 @@ impl PrintProgramError for PredictChainError {
         E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
     {
         match self {
-            PredictChainError::GenericError => msg!("Error: PredictChain generic error message"),
-            // PredictChainError::AlreadyInUse => msg!("Error: PredictChain account already in use"),
-            // PredictChainError::DepositAccountInUse => msg!("Error: Deposit account already in use"),
-            // PredictChainError::TokenMintInUse => msg!("Error: Token account already in use"),
-            // PredictChainError::InvalidAuthorityData => {
-            //     msg!("Error: Failed to generate program account because of invalid data")
-            // }
-            // PredictChainError::InvalidAuthorityAccount => msg!("Error: Invalid authority account provided"),
-            // PredictChainError::NotRentExempt => msg!("Error: Lamport balance below rent-exempt threshold"),
-            // PredictChainError::InvalidTokenMint => msg!("Error: Input token mint account is not valid"),
-            // PredictChainError::InvalidAmount => msg!("Error: Amount should be more than zero"),
-            // PredictChainError::WrongDeciderAccount => msg!("Error: Wrong decider account was sent"),
-            // PredictChainError::SignatureMissing => msg!("Error: Signature missing in transaction"),
-            // PredictChainError::DecisionAlreadyMade => {
-            //     msg!("Error: Decision was already made for this PredictChain")
-            // }
-            // PredictChainError::InvalidSlotForDecision => {
-            //     msg!("Error: Decision can't be made in current slot")
-            // }
-            // PredictChainError::InvalidSlotForDeposit => msg!("Deposit can't be made in current slot"),
-            // PredictChainError::NoDecisionMadeYet => msg!("Error: No decision has been made yet"),
+            PredictChainError::AlreadyInUse => msg!("Error: PredictChain account already in use"),
+            PredictChainError::InvalidAuthorityData => {
+                msg!("Error: Failed to generate program account because of invalid data")
+            }
+            PredictChainError::InvalidAuthorityAccount => {
+                msg!("Error: Invalid authority account provided")
+            }
+            PredictChainError::NotRentExempt => {
+                msg!("Error: Lamport balance below rent-exempt threshold")
+            }
+            PredictChainError::InvalidTokenMint => {
+                msg!("Error: Input token mint account is not valid")
+            }
+            PredictChainError::InvalidAmount => msg!("Error: Amount should be more than zero"),
+            PredictChainError::WrongResolveAuthority => {
+                msg!("Error: Wrong resolve authority account was sent")
+            }
+            PredictChainError::SignatureMissing => msg!("Error: Signature missing in transaction"),
+            PredictChainError::DecisionAlreadyMade => {
+                msg!("Error: Decision was already made for this PredictChain")
+            }
+            PredictChainError::NoDecisionMadeYet => msg!("Error: No decision has been made yet"),
+            PredictChainError::InvalidSide => {
+                msg!("Error: Side index is not an outcome of the event")
+            }
+            PredictChainError::WrongOwner => {
+                msg!("Error: Account is owned by the wrong program or user")
+            }
+            PredictChainError::EventClosed => msg!("Error: Event is no longer open"),
+            PredictChainError::OutsideTradingWindow => {
+                msg!("Error: Trading is outside the event window")
+            }
+            PredictChainError::SlippageExceeded => {
+                msg!("Error: Price moved beyond the accepted cost or proceeds")
+            }
+            PredictChainError::InvalidOutcomes => {
+                msg!("Error: Invalid number or labels of outcomes")
+            }
+            PredictChainError::InvalidBounds => {
+                msg!("Error: Invalid scalar bounds or trading window")
+            }
+            PredictChainError::WrongEventType => {
+                msg!("Error: Instruction does not apply to the event type")
+            }
+            PredictChainError::InvalidPricingMode => {
+                msg!("Error: Instruction does not apply to the event pricing mode")
+            }
+            PredictChainError::WrongAccountAddress => {
+                msg!("Error: Account is not at the address expected by the event")
+            }
+            PredictChainError::InvalidCollateralAccount => {
+                msg!("Error: Collateral vault or token account missing or invalid")
+            }
+            PredictChainError::InvalidTokenProgram => {
+                msg!("Error: Token program account is not the SPL Token program")
+            }
+            PredictChainError::InvalidSystemProgram => {
+                msg!("Error: System program account is not the System program")
+            }
+            PredictChainError::InsufficientFunds => {
+                msg!("Error: Event pool is too small for the payout")
+            }
+            PredictChainError::NotRedeemable => msg!("Error: Outcome has nothing to pay out"),
+            PredictChainError::InvalidMetadata => {
+                msg!("Error: Metadata string longer than its maximum length")
+            }
+            PredictChainError::MetadataLocked => {
+                msg!("Error: Metadata can't be edited once trading opens")
+            }
+            PredictChainError::WrongCreator => msg!("Error: Signer is not the event creator"),
+            PredictChainError::InsufficientLiquidity => {
+                msg!("Error: Market maker reserves can't cover the trade")
+            }
+            PredictChainError::MathOverflow => msg!("Error: Arithmetic overflow"),
+            PredictChainError::EventNotVoided => msg!("Error: Event has not been voided"),
+            PredictChainError::UninitializedAccount => {
+                msg!("Error: Event account is not initialized")
+            }
+            PredictChainError::InvalidAccountSize => {
+                msg!("Error: Account data length does not match the event")
+            }
+            PredictChainError::InvalidFee => msg!("Error: Fee rates exceed the maximum"),
+            PredictChainError::WrongFeeRecipient => {
+                msg!("Error: Signer is not a fee recipient of the event")
+            }
+            PredictChainError::ProgramPaused => msg!("Error: Program is paused"),
+            PredictChainError::WrongAdmin => msg!("Error: Signer is not the config admin"),
+            PredictChainError::NoPendingResolveAuthority => {
+                msg!("Error: No resolve authority is pending for the event")
+            }
+            PredictChainError::InvalidResolvers => msg!("Error: Resolver set or threshold is invalid"),
+            PredictChainError::WrongResolver => msg!("Error: Signer is not a resolver of the event"),
+            PredictChainError::ResolverVoteRequired => {
+                msg!("Error: Event is resolved by votes of its resolver set")
+            }
+            PredictChainError::InvalidProposal => msg!("Error: Event does not take this proposal"),
+            PredictChainError::ProposalRequired => msg!("Error: Event is resolved by bonded proposals"),
+            PredictChainError::WrongProposalStatus => {
+                msg!("Error: Proposal status does not allow the instruction")
+            }
+            PredictChainError::DisputeWindowClosed => msg!("Error: Dispute window has ended"),
+            PredictChainError::DisputeWindowOpen => msg!("Error: Dispute window has not ended"),
+            PredictChainError::InvalidOracle => msg!("Error: Oracle settings or price account are invalid"),
+            PredictChainError::OracleRequired => msg!("Error: Event is resolved from its oracle"),
+            PredictChainError::TradingNotClosed => msg!("Error: Event trading window has not closed"),
+            PredictChainError::StalePrice => msg!("Error: Oracle price is stale"),
+            PredictChainError::UncertainPrice => {
+                msg!("Error: Oracle price is not trading or its confidence is too wide")
+            }
+            PredictChainError::WrongUpgradeAuthority => {
+                msg!("Error: Signer is not the upgrade authority of the program")
+            }
         }
     }
 }
@@ -5,31 +5,503 @@ use solana_program::{
     instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
     pubkey::Pubkey,
+    system_program, sysvar,
+};
+use crate::{
+    address,
+    state::{EventType, PricingMode, Resolution},
 };
 
 /// Arguments for PurchaseSharesArgs
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct PurchaseSharesArgs {
-    /// index of user's desired event side: 0-YES, 1-NO
+    /// index of user's desired event outcome
     pub side_index: u8,
     /// number of tokens to mint
+    pub num_tokens: u64,
+    /// maximum cost in lamports the user accepts to pay, fees included
+    pub max_cost: u64
+}
+
+/// Metadata of an event, for CreateEvent and SetEventMetadata
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct EventMetadataArgs {
+    /// title of the event
+    pub title: String,
+    /// description of the event
+    pub description: String,
+    /// league or competition the event belongs to
+    pub league: String,
+    /// category of the event
+    pub category: String,
+    /// URI of the image of the first outcome
+    pub image0: String,
+    /// URI of the image of the second outcome
+    pub image1: String
+}
+
+/// Arguments for CreateEvent
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CreateEventArgs {
+    /// identifier chosen by the creator, from which the addresses of the
+    /// event accounts are derived
+    pub event_id: u64,
+    /// account allowed to resolve the event, unless a resolver set decides it
+    pub resolve_authority: Pubkey,
+    /// accounts voting on the resolution of the event, empty for the resolve
+    /// authority to decide alone
+    pub resolvers: Vec<Pubkey>,
+    /// number of matching resolver votes settling the event, ignored without
+    /// resolvers
+    pub resolution_threshold: u8,
+    /// seconds during which a proposed resolution can be disputed, zero for
+    /// the resolve authority to decide without proposals
+    pub dispute_window: i64,
+    /// lamports posted to propose or to dispute a resolution, ignored without
+    /// a dispute window
+    pub proposal_bond: u64,
+    /// price account resolving the event once trading closes, owned by the
+    /// oracle program of the config, default for none
+    pub oracle: Pubkey,
+    /// oracle price from which a binary event resolves to its first outcome,
+    /// ignored for scalar events which settle on the price itself
+    pub strike: i64,
+    /// exponent of the oracle price, the strike and the scalar bounds being
+    /// expressed with it
+    pub price_expo: i32,
+    /// seconds between the publication of the oracle price and the resolution
+    pub max_staleness: i64,
+    /// widest confidence interval of the oracle price accepted, in basis
+    /// points of the price
+    pub max_confidence_bps: u16,
+    /// price of a single share in lamports
+    pub price_per_share: u64,
+    /// how purchased shares are priced
+    pub pricing_mode: PricingMode,
+    /// number of shares seeded in each constant-product reserve, or the
+    /// LMSR liquidity parameter b in shares, ignored for fixed price events
+    pub liquidity: u64,
+    /// creator fee rate on trades in basis points, the protocol fee rate is
+    /// taken from the config
+    pub creator_fee_bps: u16,
+    /// labels of the event outcomes, one token mint is created per outcome
+    pub outcome_labels: Vec<String>,
+    /// what the event settles on, scalar events have a LONG then a SHORT outcome
+    pub event_type: EventType,
+    /// value paying everything to SHORT, ignored for categorical events
+    pub lower_bound: i64,
+    /// value paying everything to LONG, ignored for categorical events
+    pub upper_bound: i64,
+    /// unix timestamp from which shares can be traded
+    pub open_time: i64,
+    /// unix timestamp from which shares can no longer be traded
+    pub close_time: i64,
+    /// metadata stored in the metadata account
+    pub metadata: EventMetadataArgs
+}
+
+/// Arguments for ResolveEvent
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ResolveEventArgs {
+    /// index of the winning event outcome
+    pub winning_side: u8
+}
+
+/// Arguments for ResolveScalarEvent
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ResolveScalarEventArgs {
+    /// observed value, clamped to the event bounds
+    pub value: i64
+}
+
+/// Arguments for RedeemShares
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RedeemSharesArgs {
+    /// number of winning tokens to burn
     pub num_tokens: u64
 }
 
+/// Arguments for ClaimRefund
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ClaimRefundArgs {
+    /// number of tokens to burn
+    pub num_tokens: u64
+}
+
+/// Arguments for SellShares
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SellSharesArgs {
+    /// index of the sold event outcome
+    pub side_index: u8,
+    /// number of tokens to burn
+    pub num_tokens: u64,
+    /// minimum proceeds in lamports the user accepts to receive, net of fees
+    pub min_proceeds: u64
+}
+
+/// Arguments for MintCompleteSet
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MintCompleteSetArgs {
+    /// number of sets of one token per outcome to mint
+    pub num_sets: u64
+}
+
+/// Arguments for MergeCompleteSet
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MergeCompleteSetArgs {
+    /// number of sets of one token per outcome to burn
+    pub num_sets: u64
+}
+
+/// Arguments for InitializeConfig and UpdateConfig
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ConfigArgs {
+    /// account allowed to update the config and to pause the program
+    pub admin: Pubkey,
+    /// protocol fee rate on trades in basis points of the events created from now on
+    pub protocol_fee_bps: u16,
+    /// account allowed to withdraw the protocol fees of the events created from now on
+    pub protocol_fee_recipient: Pubkey,
+    /// program owning the price accounts of the oracle events created from
+    /// now on, default to disable oracle events
+    pub oracle_program: Pubkey
+}
+
+/// Arguments for SetPaused
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SetPausedArgs {
+    /// whether state-changing instructions fail
+    pub paused: bool
+}
+
+/// Arguments for ProposeResolveAuthority
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProposeResolveAuthorityArgs {
+    /// account to hand the resolution over to, default to cancel a proposal
+    pub new_resolve_authority: Pubkey
+}
+
+/// Arguments for VoteResolution
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct VoteResolutionArgs {
+    /// resolution voted for, replacing any previous vote of the resolver
+    pub resolution: Resolution
+}
+
+/// Arguments for ProposeResolution
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProposeResolutionArgs {
+    /// winning side of a categorical event or value of a scalar event
+    pub resolution: Resolution
+}
+
+/// Arguments for SettleDispute
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SettleDisputeArgs {
+    /// resolution decided by the resolve authority
+    pub resolution: Resolution
+}
+
 /// Instruction definition
 #[derive(BorshSerialize, BorshDeserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum PredictChainInstruction {
-    /// Purchase shares which mints tokens to a user
-    /// 
-    /// 0. `[s]` User signer
+    /// Purchase shares of an outcome which mints tokens to a user
+    ///
+    /// The cost of the shares, as given by the event pricing mode, is
+    /// transferred from the user to the pool and the trading fees on it to the
+    /// fee vault. Fails outside the event trading window.
+    ///
+    /// 0. `[ws]` User signer
     /// 1. `[]` Authority (Program Derived Address)
     /// 2. `[w]` Event account
-    /// 3. `[w]` YES token mint
-    /// 4. `[w]` NO token mint
-    /// 5. `[w]` Associated token account to receive shares
-    /// 6. `[w]` Payment PDA
-    /// 7. `[]` Token program id
-    PurchaseShares(PurchaseSharesArgs)
+    /// 3. `[w]` Token mint of the purchased outcome
+    /// 4. `[w]` Associated token account to receive shares
+    /// 5. `[]` System program id
+    /// 6. `[]` Token program id
+    /// 7. `[w]` Fee vault of the event
+    /// 8. `[]` Config account
+    /// 9. `[w]` Collateral vault, SPL collateral events only
+    /// 10. `[w]` Collateral token account paying the cost, SPL collateral events only
+    PurchaseShares(PurchaseSharesArgs),
+
+    /// Initialize an event account and the token mints of its N outcomes
+    ///
+    /// The event, metadata, mint, fee vault, resolution and collateral vault
+    /// accounts are allocated by the program at their program derived
+    /// addresses, the creator paying for them. The fee vault of an SPL
+    /// collateral event is a token account, that of a lamport event a
+    /// data-less account of the program.
+    ///
+    /// 0. `[ws]` Creator
+    /// 1. `[]` Authority (Program Derived Address)
+    /// 2. `[w]` Event account, uninitialized
+    /// 3. `[w]` Metadata account, uninitialized
+    /// - 4..4+N. `[w]` Token mints of the outcomes in order, uninitialized
+    /// - 4+N. `[]` Rent sysvar
+    /// - 5+N. `[]` Token program id
+    /// - 6+N. `[]` System program
+    /// - 7+N. `[w]` Fee vault, uninitialized
+    /// - 8+N. `[]` Config account
+    /// - 9+N. `[w]` Resolution account, uninitialized, events with resolvers only
+    /// - 9+N or 10+N. `[]` Collateral token mint, optional
+    /// - 10+N or 11+N. `[w]` Collateral vault, uninitialized, required with a collateral mint
+    CreateEvent(CreateEventArgs),
+
+    /// Resolve a categorical event, after which no more shares can be purchased
+    ///
+    /// Only for events the resolve authority decides alone: resolver sets
+    /// vote with `VoteResolution`, bonded proposals go through
+    /// `ProposeResolution` and oracle events settle with `ResolveFromOracle`.
+    ///
+    /// 0. `[s]` Resolve authority stored in the event account
+    /// 1. `[w]` Event account
+    /// 2. `[]` Config account
+    ResolveEvent(ResolveEventArgs),
+
+    /// Redeem shares of a resolved event for a pro-rata part of the payout
+    /// pool of their outcome
+    ///
+    /// When nobody holds the outcome an event settles on, its pool is split
+    /// between the other outcomes as for a voided event instead, and their
+    /// shares are redeemed from it.
+    ///
+    /// The user token account must have approved the authority as delegate
+    /// for at least `num_tokens`.
+    ///
+    /// 0. `[ws]` User signer, owner of the token account and receiver of the payout
+    /// 1. `[]` Authority (Program Derived Address)
+    /// 2. `[w]` Event account
+    /// 3. `[w]` Token mint of the redeemed outcome
+    /// 4. `[w]` Token account to burn shares from
+    /// 5. `[]` Token program id
+    /// 6. `[]` Config account
+    /// 7. `[w]` Collateral vault, SPL collateral events only
+    /// 8. `[w]` Collateral token account receiving the payout, SPL collateral events only
+    RedeemShares(RedeemSharesArgs),
+
+    /// Sell shares back to the event pool at the current price before resolution
+    /// and within the event trading window, the trading fees on the proceeds
+    /// going to the fee vault
+    ///
+    /// The user token account must have approved the authority as delegate
+    /// for at least `num_tokens`.
+    ///
+    /// 0. `[ws]` User signer, owner of the token account and receiver of the proceeds
+    /// 1. `[]` Authority (Program Derived Address)
+    /// 2. `[w]` Event account
+    /// 3. `[w]` Token mint of the sold outcome
+    /// 4. `[w]` Token account to burn shares from
+    /// 5. `[]` Token program id
+    /// 6. `[w]` Fee vault of the event
+    /// 7. `[]` Config account
+    /// 8. `[w]` Collateral vault, SPL collateral events only
+    /// 9. `[w]` Collateral token account receiving the proceeds, SPL collateral events only
+    SellShares(SellSharesArgs),
+
+    /// Deposit `price_per_share` lamports per set and receive one token of
+    /// each of the N outcomes for each, only for market maker priced events
+    ///
+    /// `num_sets * price_per_share` is transferred from the user.
+    ///
+    /// 0. `[ws]` User signer
+    /// 1. `[]` Authority (Program Derived Address)
+    /// 2. `[w]` Event account
+    /// 3. `[]` System program id
+    /// 4. `[]` Token program id
+    /// - 5..5+N. `[w]` Token mints of the outcomes in order
+    /// - 5+N..5+2N. `[w]` Token accounts to receive tokens of the outcomes in order
+    /// - 5+2N. `[]` Config account
+    /// - 6+2N. `[w]` Collateral vault, SPL collateral events only
+    /// - 7+2N. `[w]` Collateral token account paying the cost, SPL collateral events only
+    MintCompleteSet(MintCompleteSetArgs),
+
+    /// Burn sets of one token per outcome and withdraw `price_per_share`
+    /// lamports per set while the event is open, only for market maker priced
+    /// events
+    ///
+    /// All user token accounts must have approved the authority as delegate
+    /// for at least `num_sets`.
+    ///
+    /// 0. `[ws]` User signer, owner of the token accounts and receiver of the collateral
+    /// 1. `[]` Authority (Program Derived Address)
+    /// 2. `[w]` Event account
+    /// 3. `[]` Token program id
+    /// - 4..4+N. `[w]` Token mints of the outcomes in order
+    /// - 4+N..4+2N. `[w]` Token accounts to burn tokens of the outcomes from, in order
+    /// - 4+2N. `[]` Config account
+    /// - 5+2N. `[w]` Collateral vault, SPL collateral events only
+    /// - 6+2N. `[w]` Collateral token account receiving the collateral, SPL collateral events only
+    MergeCompleteSet(MergeCompleteSetArgs),
+
+    /// Resolve a scalar event with a numeric value, splitting the pool
+    /// between LONG and SHORT proportionally to where it falls between the
+    /// bounds
+    ///
+    /// Like `ResolveEvent`, it is rejected for events with a resolver set, a
+    /// dispute window or a price account.
+    ///
+    /// 0. `[s]` Resolve authority stored in the event account
+    /// 1. `[w]` Event account
+    /// 2. `[]` Config account
+    ResolveScalarEvent(ResolveScalarEventArgs),
+
+    /// Void an open event, after which no more shares can be traded and the
+    /// pool is set aside per outcome in proportion to the collateral paid for it
+    ///
+    /// The resolve authority can't void events decided by a resolver set, nor
+    /// events decided by bonded proposals once a resolution is proposed. It
+    /// can void oracle events, in case their price account stops publishing.
+    ///
+    /// 0. `[s]` Resolve authority stored in the event account
+    /// 1. `[w]` Event account
+    /// 2. `[]` Config account
+    VoidEvent,
+
+    /// Claim a refund for shares of any outcome of a voided event, paying a
+    /// pro-rata part of the collateral set aside for their outcome
+    ///
+    /// The user token account must have approved the authority as delegate
+    /// for at least `num_tokens`.
+    ///
+    /// 0. `[ws]` User signer, owner of the token account and receiver of the refund
+    /// 1. `[]` Authority (Program Derived Address)
+    /// 2. `[w]` Event account
+    /// 3. `[w]` Token mint of the refunded outcome
+    /// 4. `[w]` Token account to burn shares from
+    /// 5. `[]` Token program id
+    /// 6. `[]` Config account
+    /// 7. `[w]` Collateral vault, SPL collateral events only
+    /// 8. `[w]` Collateral token account receiving the refund, SPL collateral events only
+    ClaimRefund(ClaimRefundArgs),
+
+    /// Replace the metadata of an event, only before its trading window opens
+    ///
+    /// 0. `[s]` Creator stored in the event account
+    /// 1. `[]` Event account
+    /// 2. `[w]` Metadata account of the event
+    /// 3. `[]` Config account
+    SetEventMetadata(EventMetadataArgs),
+
+    /// Withdraw the fees collected for the signer, the creator fees if it is
+    /// the event creator and the protocol fees if it is the protocol fee
+    /// recipient
+    ///
+    /// 0. `[ws]` Fee recipient signer, receiver of the lamport fees
+    /// 1. `[]` Authority (Program Derived Address)
+    /// 2. `[w]` Event account
+    /// 3. `[w]` Fee vault of the event
+    /// 4. `[]` Token program id
+    /// 5. `[]` Config account
+    /// 6. `[w]` Collateral token account receiving the fees, SPL collateral events only
+    WithdrawFees,
+
+    /// Initialize the program config, allocating its account unless it
+    /// already is, only by the upgrade authority of the program
+    ///
+    /// 0. `[ws]` Upgrade authority of the program, payer of the config account
+    /// 1. `[w]` Config account (Program Derived Address)
+    /// 2. `[]` System program id
+    /// 3. `[]` Program data account of the program
+    InitializeConfig(ConfigArgs),
+
+    /// Update the admin and the fee settings of the program config
+    ///
+    /// 0. `[s]` Admin stored in the config account
+    /// 1. `[w]` Config account
+    UpdateConfig(ConfigArgs),
+
+    /// Pause or unpause every state-changing instruction but the config ones
+    /// and DisputeResolution
+    ///
+    /// 0. `[s]` Admin stored in the config account
+    /// 1. `[w]` Config account
+    SetPaused(SetPausedArgs),
+
+    /// Propose a new resolve authority of an event, which takes over once it
+    /// accepts
+    ///
+    /// 0. `[s]` Resolve authority of the event
+    /// 1. `[w]` Event account
+    /// 2. `[]` Config account
+    ProposeResolveAuthority(ProposeResolveAuthorityArgs),
+
+    /// Accept the resolve authority of an event proposed to the signer
+    ///
+    /// 0. `[s]` Pending resolve authority of the event
+    /// 1. `[w]` Event account
+    /// 2. `[]` Config account
+    AcceptResolveAuthority,
+
+    /// Record the vote of a resolver on the resolution of an event once its
+    /// trading window has closed, settling the event once
+    /// `resolution_threshold` resolvers vote the same way
+    ///
+    /// Resolvers may change their vote until the event is settled. Conflicting
+    /// votes are recorded alongside and settle nothing. Scalar values are
+    /// clamped to the event bounds before they are recorded.
+    ///
+    /// 0. `[s]` Resolver of the event
+    /// 1. `[w]` Event account
+    /// 2. `[w]` Resolution account of the event
+    /// 3. `[]` Config account
+    VoteResolution(VoteResolutionArgs),
+
+    /// Propose the resolution of an event taking proposals once its trading
+    /// window has closed, posting the proposal bond into the event account
+    ///
+    /// 0. `[ws]` Proposer, paying the bond
+    /// 1. `[w]` Event account
+    /// 2. `[]` System program id
+    /// 3. `[]` Config account
+    ProposeResolution(ProposeResolutionArgs),
+
+    /// Dispute the proposed resolution of an event within the dispute window,
+    /// posting a bond matching the proposal bond, after which the resolve
+    /// authority decides the event
+    ///
+    /// Disputes are accepted while the program is paused, the dispute window
+    /// running on regardless.
+    ///
+    /// 0. `[ws]` Disputer, paying the bond
+    /// 1. `[w]` Event account
+    /// 2. `[]` System program id
+    /// 3. `[]` Config account
+    DisputeResolution,
+
+    /// Settle an event on its undisputed proposed resolution once the dispute
+    /// window has ended, returning the bond to the proposer
+    ///
+    /// 0. `[w]` Proposer of the resolution
+    /// 1. `[w]` Event account
+    /// 2. `[]` Config account
+    FinalizeResolution,
+
+    /// Settle an event whose proposed resolution is disputed. Both bonds go to
+    /// the proposer if the resolve authority confirms the proposal, to the
+    /// disputer otherwise.
+    ///
+    /// 0. `[s]` Resolve authority stored in the event account
+    /// 1. `[w]` Event account
+    /// 2. `[w]` Proposer of the resolution
+    /// 3. `[w]` Disputer of the resolution
+    /// 4. `[]` Config account
+    SettleDispute(SettleDisputeArgs),
+
+    /// Settle an event from its oracle once trading has closed, anyone may
+    /// crank it
+    ///
+    /// A binary event resolves to its first outcome if the price is at least
+    /// the strike, to its second otherwise. A scalar event settles on the
+    /// price. The price must be published after the close time, at most
+    /// `max_staleness` seconds ago, and be trading with a confidence interval
+    /// within `max_confidence_bps` of the price. The price account must be
+    /// owned by the oracle program of the config when the event was created.
+    ///
+    /// 0. `[w]` Event account
+    /// 1. `[]` Oracle price account of the event
+    /// 2. `[]` Config account
+    ResolveFromOracle
 }
 
 /// Create `PurchaseShares` instruction
@@ -39,26 +511,659 @@ pub fn purchase_shares(
     user_signer: &Pubkey,
     authority: &Pubkey,
     event: &Pubkey,
-    yes_mint: &Pubkey,
-    no_mint: &Pubkey,
+    mint: &Pubkey,
     user_token_account: &Pubkey,
-    payment_account: &Pubkey,
     token_program_id: &Pubkey,
+    fee_vault: &Pubkey,
+    collateral: Option<(&Pubkey, &Pubkey)>,
     args: PurchaseSharesArgs
 ) -> Result<Instruction, ProgramError> {
     let init_data = PredictChainInstruction::PurchaseShares(args);
     let data = init_data.try_to_vec()?;
 
+    let (config, _) = address::find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*user_signer, true),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*event, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*fee_vault, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    if let Some((collateral_vault, user_collateral_account)) = collateral {
+        accounts.push(AccountMeta::new(*collateral_vault, false));
+        accounts.push(AccountMeta::new(*user_collateral_account, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `CreateEvent` instruction, initializing the accounts of the event
+/// found from the creator and `args.event_id`. `collateral_mint` is the SPL
+/// token events priced in a token are denominated in, for which a collateral
+/// vault is created as well.
+pub fn create_event(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    token_program_id: &Pubkey,
+    collateral_mint: Option<&Pubkey>,
+    args: CreateEventArgs
+) -> Result<Instruction, ProgramError> {
+    let (event, _) = address::find_event_address(program_id, creator, args.event_id);
+    let (metadata, _) = address::find_metadata_address(program_id, &event);
+    let (authority, _) = address::find_authority_address(program_id, &event);
+    let (fee_vault, _) = address::find_fee_vault_address(program_id, &event);
+    let (config, _) = address::find_config_address(program_id);
+    let num_outcomes = args.outcome_labels.len();
+    let has_resolvers = !args.resolvers.is_empty();
+    let init_data = PredictChainInstruction::CreateEvent(args);
+    let data = init_data.try_to_vec()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new_readonly(authority, false),
+        AccountMeta::new(event, false),
+        AccountMeta::new(metadata, false),
+    ];
+    for outcome_index in 0..num_outcomes as u8 {
+        let (mint, _) = address::find_mint_address(program_id, &event, outcome_index);
+        accounts.push(AccountMeta::new(mint, false));
+    }
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    accounts.push(AccountMeta::new_readonly(*token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new(fee_vault, false));
+    accounts.push(AccountMeta::new_readonly(config, false));
+    if has_resolvers {
+        let (resolution, _) = address::find_resolution_address(program_id, &event);
+        accounts.push(AccountMeta::new(resolution, false));
+    }
+    if let Some(collateral_mint) = collateral_mint {
+        let (collateral_vault, _) = address::find_vault_address(program_id, &event);
+        accounts.push(AccountMeta::new_readonly(*collateral_mint, false));
+        accounts.push(AccountMeta::new(collateral_vault, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `ResolveEvent` instruction
+pub fn resolve_event(
+    program_id: &Pubkey,
+    resolve_authority: &Pubkey,
+    event: &Pubkey,
+    args: ResolveEventArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::ResolveEvent(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
     let accounts = vec![
-        AccountMeta::new_readonly(*user_signer, true),
+        AccountMeta::new_readonly(*resolve_authority, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `RedeemShares` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn redeem_shares(
+    program_id: &Pubkey,
+    user_signer: &Pubkey,
+    authority: &Pubkey,
+    event: &Pubkey,
+    winning_mint: &Pubkey,
+    user_token_account: &Pubkey,
+    token_program_id: &Pubkey,
+    collateral: Option<(&Pubkey, &Pubkey)>,
+    args: RedeemSharesArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::RedeemShares(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*user_signer, true),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*event, false),
+        AccountMeta::new(*winning_mint, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    if let Some((collateral_vault, user_collateral_account)) = collateral {
+        accounts.push(AccountMeta::new(*collateral_vault, false));
+        accounts.push(AccountMeta::new(*user_collateral_account, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `SellShares` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn sell_shares(
+    program_id: &Pubkey,
+    user_signer: &Pubkey,
+    authority: &Pubkey,
+    event: &Pubkey,
+    mint: &Pubkey,
+    user_token_account: &Pubkey,
+    token_program_id: &Pubkey,
+    fee_vault: &Pubkey,
+    collateral: Option<(&Pubkey, &Pubkey)>,
+    args: SellSharesArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::SellShares(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*user_signer, true),
         AccountMeta::new_readonly(*authority, false),
         AccountMeta::new(*event, false),
-        AccountMeta::new(*yes_mint, false),
-        AccountMeta::new(*no_mint, false),
+        AccountMeta::new(*mint, false),
         AccountMeta::new(*user_token_account, false),
-        AccountMeta::new(*payment_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*fee_vault, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    if let Some((collateral_vault, user_collateral_account)) = collateral {
+        accounts.push(AccountMeta::new(*collateral_vault, false));
+        accounts.push(AccountMeta::new(*user_collateral_account, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `MintCompleteSet` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn mint_complete_set(
+    program_id: &Pubkey,
+    user_signer: &Pubkey,
+    authority: &Pubkey,
+    event: &Pubkey,
+    outcome_mints: &[Pubkey],
+    user_token_accounts: &[Pubkey],
+    token_program_id: &Pubkey,
+    collateral: Option<(&Pubkey, &Pubkey)>,
+    args: MintCompleteSetArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::MintCompleteSet(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*user_signer, true),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(*token_program_id, false)
     ];
+    for mint in outcome_mints {
+        accounts.push(AccountMeta::new(*mint, false));
+    }
+    for user_token_account in user_token_accounts {
+        accounts.push(AccountMeta::new(*user_token_account, false));
+    }
+    accounts.push(AccountMeta::new_readonly(config, false));
+    if let Some((collateral_vault, user_collateral_account)) = collateral {
+        accounts.push(AccountMeta::new(*collateral_vault, false));
+        accounts.push(AccountMeta::new(*user_collateral_account, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `MergeCompleteSet` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn merge_complete_set(
+    program_id: &Pubkey,
+    user_signer: &Pubkey,
+    authority: &Pubkey,
+    event: &Pubkey,
+    outcome_mints: &[Pubkey],
+    user_token_accounts: &[Pubkey],
+    token_program_id: &Pubkey,
+    collateral: Option<(&Pubkey, &Pubkey)>,
+    args: MergeCompleteSetArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::MergeCompleteSet(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*user_signer, true),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(*token_program_id, false)
+    ];
+    for mint in outcome_mints {
+        accounts.push(AccountMeta::new(*mint, false));
+    }
+    for user_token_account in user_token_accounts {
+        accounts.push(AccountMeta::new(*user_token_account, false));
+    }
+    accounts.push(AccountMeta::new_readonly(config, false));
+    if let Some((collateral_vault, user_collateral_account)) = collateral {
+        accounts.push(AccountMeta::new(*collateral_vault, false));
+        accounts.push(AccountMeta::new(*user_collateral_account, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `ResolveScalarEvent` instruction
+pub fn resolve_scalar_event(
+    program_id: &Pubkey,
+    resolve_authority: &Pubkey,
+    event: &Pubkey,
+    args: ResolveScalarEventArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::ResolveScalarEvent(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*resolve_authority, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `VoidEvent` instruction
+pub fn void_event(
+    program_id: &Pubkey,
+    resolve_authority: &Pubkey,
+    event: &Pubkey
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::VoidEvent;
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*resolve_authority, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `ClaimRefund` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn claim_refund(
+    program_id: &Pubkey,
+    user_signer: &Pubkey,
+    authority: &Pubkey,
+    event: &Pubkey,
+    mint: &Pubkey,
+    user_token_account: &Pubkey,
+    token_program_id: &Pubkey,
+    collateral: Option<(&Pubkey, &Pubkey)>,
+    args: ClaimRefundArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::ClaimRefund(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*user_signer, true),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*event, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    if let Some((collateral_vault, user_collateral_account)) = collateral {
+        accounts.push(AccountMeta::new(*collateral_vault, false));
+        accounts.push(AccountMeta::new(*user_collateral_account, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `SetEventMetadata` instruction
+pub fn set_event_metadata(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    event: &Pubkey,
+    metadata: &Pubkey,
+    args: EventMetadataArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::SetEventMetadata(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*creator, true),
+        AccountMeta::new_readonly(*event, false),
+        AccountMeta::new(*metadata, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `WithdrawFees` instruction
+pub fn withdraw_fees(
+    program_id: &Pubkey,
+    recipient: &Pubkey,
+    authority: &Pubkey,
+    event: &Pubkey,
+    fee_vault: &Pubkey,
+    token_program_id: &Pubkey,
+    destination: Option<&Pubkey>
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::WithdrawFees;
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*recipient, true),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*event, false),
+        AccountMeta::new(*fee_vault, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    if let Some(destination) = destination {
+        accounts.push(AccountMeta::new(*destination, false));
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `InitializeConfig` instruction
+pub fn initialize_config(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    args: ConfigArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::InitializeConfig(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let (program_data, _) = address::find_program_data_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(*upgrade_authority, true),
+        AccountMeta::new(config, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(program_data, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `UpdateConfig` instruction
+pub fn update_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    args: ConfigArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::UpdateConfig(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `SetPaused` instruction
+pub fn set_paused(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    args: SetPausedArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::SetPaused(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `ProposeResolveAuthority` instruction
+pub fn propose_resolve_authority(
+    program_id: &Pubkey,
+    resolve_authority: &Pubkey,
+    event: &Pubkey,
+    args: ProposeResolveAuthorityArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::ProposeResolveAuthority(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*resolve_authority, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `AcceptResolveAuthority` instruction
+pub fn accept_resolve_authority(
+    program_id: &Pubkey,
+    pending_resolve_authority: &Pubkey,
+    event: &Pubkey
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::AcceptResolveAuthority;
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*pending_resolve_authority, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `VoteResolution` instruction
+pub fn vote_resolution(
+    program_id: &Pubkey,
+    resolver: &Pubkey,
+    event: &Pubkey,
+    resolution: &Pubkey,
+    args: VoteResolutionArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::VoteResolution(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*resolver, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new(*resolution, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `ProposeResolution` instruction
+pub fn propose_resolution(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    event: &Pubkey,
+    args: ProposeResolutionArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::ProposeResolution(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(*proposer, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `DisputeResolution` instruction
+pub fn dispute_resolution(
+    program_id: &Pubkey,
+    disputer: &Pubkey,
+    event: &Pubkey
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::DisputeResolution;
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(*disputer, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `FinalizeResolution` instruction
+pub fn finalize_resolution(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    event: &Pubkey
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::FinalizeResolution;
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(*proposer, false),
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `SettleDispute` instruction
+pub fn settle_dispute(
+    program_id: &Pubkey,
+    resolve_authority: &Pubkey,
+    event: &Pubkey,
+    proposer: &Pubkey,
+    disputer: &Pubkey,
+    args: SettleDisputeArgs
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::SettleDispute(args);
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*resolve_authority, true),
+        AccountMeta::new(*event, false),
+        AccountMeta::new(*proposer, false),
+        AccountMeta::new(*disputer, false),
+        AccountMeta::new_readonly(config, false)
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Create `ResolveFromOracle` instruction
+pub fn resolve_from_oracle(
+    program_id: &Pubkey,
+    event: &Pubkey,
+    oracle: &Pubkey
+) -> Result<Instruction, ProgramError> {
+    let init_data = PredictChainInstruction::ResolveFromOracle;
+    let data = init_data.try_to_vec()?;
+
+    let (config, _) = address::find_config_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(*event, false),
+        AccountMeta::new_readonly(*oracle, false),
+        AccountMeta::new_readonly(config, false)
+    ];
     Ok(Instruction {
         program_id: *program_id,
         accounts,
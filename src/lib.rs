@@ -1,8 +1,12 @@
 //! PredictChain
 #![deny(missing_docs)]
 
+pub mod address;
 pub mod error;
 pub mod instruction;
+pub mod math;
+pub mod oracle;
+pub mod pricing;
 pub mod processor;
 pub mod state;
 
@@ -0,0 +1,30 @@
+//! Overflow-checked arithmetic for pool, volume and payout accounting
+
+use crate::error::PredictChainError;
+use std::convert::TryFrom;
+
+/// `a + b`, or `MathOverflow` if the sum doesn't fit a `u64`
+pub fn add(a: u64, b: u64) -> Result<u64, PredictChainError> {
+    a.checked_add(b).ok_or(PredictChainError::MathOverflow)
+}
+
+/// `a - b`, or `MathOverflow` if `b` is greater than `a`
+pub fn sub(a: u64, b: u64) -> Result<u64, PredictChainError> {
+    a.checked_sub(b).ok_or(PredictChainError::MathOverflow)
+}
+
+/// `a * b`, or `MathOverflow` if the product doesn't fit a `u64`
+pub fn mul(a: u64, b: u64) -> Result<u64, PredictChainError> {
+    a.checked_mul(b).ok_or(PredictChainError::MathOverflow)
+}
+
+/// `value * numerator / denominator` rounded down, computed without
+/// intermediate overflow. Fails with `MathOverflow` if the denominator is zero
+/// or the result doesn't fit a `u64`.
+pub fn mul_div(value: u128, numerator: u128, denominator: u128) -> Result<u64, PredictChainError> {
+    value
+        .checked_mul(numerator)
+        .and_then(|product| product.checked_div(denominator))
+        .and_then(|quotient| u64::try_from(quotient).ok())
+        .ok_or(PredictChainError::MathOverflow)
+}
@@ -0,0 +1,99 @@
+//! Reading of Pyth-style price accounts
+//!
+//! Only the fields needed to settle an event are read from the raw account
+//! data, at their offsets in the version 2 price account layout, so the
+//! program doesn't depend on an oracle client crate.
+
+use crate::{error::PredictChainError, state::EventAccount};
+use std::convert::TryInto;
+
+/// Magic number starting every price account
+pub const MAGIC: u32 = 0xa1b2_c3d4;
+
+/// Version of the price account layout
+pub const VERSION: u32 = 2;
+
+/// Account type of a price account
+pub const ACCOUNT_TYPE_PRICE: u32 = 3;
+
+/// Status of an aggregate price that is currently trading
+pub const STATUS_TRADING: u32 = 1;
+
+/// Offset of the magic number
+pub const MAGIC_OFFSET: usize = 0;
+
+/// Offset of the layout version
+pub const VERSION_OFFSET: usize = 4;
+
+/// Offset of the account type
+pub const ACCOUNT_TYPE_OFFSET: usize = 8;
+
+/// Offset of the price exponent
+pub const EXPO_OFFSET: usize = 20;
+
+/// Offset of the unix timestamp of the aggregate price
+pub const TIMESTAMP_OFFSET: usize = 96;
+
+/// Offset of the aggregate price
+pub const PRICE_OFFSET: usize = 208;
+
+/// Offset of the confidence interval of the aggregate price
+pub const CONF_OFFSET: usize = 216;
+
+/// Offset of the status of the aggregate price
+pub const STATUS_OFFSET: usize = 224;
+
+/// Minimum length of a price account holding every field read
+pub const MIN_LEN: usize = STATUS_OFFSET + 4;
+
+/// Aggregate price of a price account, worth `price * 10^expo`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceFeed {
+    /// price
+    pub price: i64,
+    /// conf, confidence interval around the price
+    pub conf: u64,
+    /// expo, exponent of the price and its confidence interval
+    pub expo: i32,
+    /// status, `STATUS_TRADING` if the price can be relied on
+    pub status: u32,
+    /// timestamp, unix timestamp at which the price was published
+    pub timestamp: i64,
+}
+
+impl PriceFeed {
+    /// Reads the aggregate price of a price account, failing with
+    /// `InvalidOracle` if the data isn't a price account
+    pub fn load(data: &[u8]) -> Result<Self, PredictChainError> {
+        if data.len() < MIN_LEN
+            || read_u32(data, MAGIC_OFFSET) != MAGIC
+            || read_u32(data, VERSION_OFFSET) != VERSION
+            || read_u32(data, ACCOUNT_TYPE_OFFSET) != ACCOUNT_TYPE_PRICE
+        {
+            return Err(PredictChainError::InvalidOracle);
+        }
+        Ok(Self {
+            price: read_u64(data, PRICE_OFFSET) as i64,
+            conf: read_u64(data, CONF_OFFSET),
+            expo: read_u32(data, EXPO_OFFSET) as i32,
+            status: read_u32(data, STATUS_OFFSET),
+            timestamp: read_u64(data, TIMESTAMP_OFFSET) as i64,
+        })
+    }
+
+    /// Check if the price is trading and its confidence interval within
+    /// `max_confidence_bps` basis points of the price
+    pub fn is_reliable(&self, max_confidence_bps: u16) -> bool {
+        self.status == STATUS_TRADING
+            && self.conf as u128 * EventAccount::BPS_DENOMINATOR
+                <= self.price.unsigned_abs() as u128 * max_confidence_bps as u128
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
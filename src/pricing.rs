@@ -0,0 +1,196 @@
+//! Share pricing functions, shared by the program and off-chain clients
+
+/// Integer square root, rounded up
+fn sqrt_ceil(value: u128) -> u128 {
+    if value == 0 {
+        return 0;
+    }
+    // Newton iteration converging from above to floor(sqrt(value)), starting
+    // from value / 2 + 1 so that x + value / x can't overflow
+    let mut x = value / 2 + 1;
+    let mut y = (x + value / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    if x * x < value {
+        x + 1
+    } else {
+        x
+    }
+}
+
+/// Cost of buying `amount` out of the `reserve_in` side of a constant-product
+/// pool of two outcome reserves.
+///
+/// The cost `c` is added to both reserves and `amount` is then withdrawn from
+/// `reserve_in`, keeping `reserve_in * reserve_out` at least constant. All
+/// values share the same unit. Returns `None` on overflow or an empty pool.
+pub fn constant_product_buy_cost(reserve_in: u64, reserve_out: u64, amount: u64) -> Option<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return None;
+    }
+    let reserve_in = reserve_in as u128;
+    let reserve_out = reserve_out as u128;
+    let amount = amount as u128;
+
+    // c^2 + c * (reserve_in + reserve_out - amount) - amount * reserve_out = 0
+    let sum = reserve_in.checked_add(reserve_out)?;
+    let linear = amount.checked_mul(reserve_out)?.checked_mul(4)?;
+    let numerator = if sum >= amount {
+        let b = sum - amount;
+        sqrt_ceil(b.checked_mul(b)?.checked_add(linear)?) - b
+    } else {
+        let b = amount - sum;
+        sqrt_ceil(b.checked_mul(b)?.checked_add(linear)?).checked_add(b)?
+    };
+    let cost = numerator / 2 + numerator % 2;
+    if cost > u64::MAX as u128 {
+        return None;
+    }
+    Some(cost as u64)
+}
+
+/// Proceeds of selling `amount` into the `reserve_in` side of a
+/// constant-product pool of two outcome reserves, rounded down.
+///
+/// `amount` is added to `reserve_in` and the proceeds `c` are then withdrawn
+/// from both reserves, keeping `reserve_in * reserve_out` at least constant.
+/// All values share the same unit. Returns `None` on overflow or an empty pool.
+pub fn constant_product_sell_proceeds(reserve_in: u64, reserve_out: u64, amount: u64) -> Option<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return None;
+    }
+    let reserve_out = reserve_out as u128;
+    let amount = amount as u128;
+
+    // c^2 - c * (reserve_in + amount + reserve_out) + amount * reserve_out = 0
+    let b = (reserve_in as u128)
+        .checked_add(amount)?
+        .checked_add(reserve_out)?;
+    let linear = amount.checked_mul(reserve_out)?.checked_mul(4)?;
+    let proceeds = (b - sqrt_ceil(b.checked_mul(b)? - linear)) / 2;
+    Some(proceeds as u64)
+}
+
+/// Fixed-point scale of the LMSR math, 1.0 == `SCALE`
+pub const SCALE: u128 = 1_000_000_000_000;
+
+/// ln(2) in fixed-point
+const LN_2: u128 = 693_147_180_560;
+
+/// Number of series terms, enough for `SCALE` precision on the reduced ranges
+const SERIES_TERMS: u128 = 16;
+
+/// e^-x for a fixed-point `x >= 0`
+fn exp_neg(x: u128) -> u128 {
+    // e^-x = 2^-k * e^-r with r in [0, ln 2)
+    let k = x / LN_2;
+    if k >= 64 {
+        return 0;
+    }
+    let r = x % LN_2;
+
+    // e^r by its Taylor series, every term is positive
+    let mut term = SCALE;
+    let mut sum = SCALE;
+    for n in 1..SERIES_TERMS {
+        term = term * r / SCALE / n;
+        sum += term;
+    }
+    (SCALE * SCALE / sum) >> k
+}
+
+/// ln(y) for a fixed-point `y >= 1`
+fn ln(y: u128) -> u128 {
+    // y = 2^k * m with m in [1, 2)
+    let mut k = 0;
+    let mut m = y;
+    while m >= 2 * SCALE {
+        m >>= 1;
+        k += 1;
+    }
+
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) in [0, 1/3)
+    let z = (m - SCALE) * SCALE / (m + SCALE);
+    let z_squared = z * z / SCALE;
+    let mut power = z;
+    let mut sum = 0;
+    for n in 0..SERIES_TERMS {
+        sum += power / (2 * n + 1);
+        power = power * z_squared / SCALE;
+    }
+    k * LN_2 + 2 * sum
+}
+
+/// LMSR cost function `C(q) = b * ln(sum(e^(q_i / b)))` in fixed-point shares,
+/// for liquidity parameter `b` and outstanding share quantities `q`.
+///
+/// Returns `None` on overflow, a zero `b` or no outcomes.
+pub fn lmsr_cost(liquidity: u64, quantities: &[u64]) -> Option<u128> {
+    if liquidity == 0 {
+        return None;
+    }
+    let max_quantity = *quantities.iter().max()?;
+
+    // C(q) = max(q) + b * ln(sum(e^((q_i - max(q)) / b))) keeps every exponent <= 0
+    let mut sum: u128 = 0;
+    for quantity in quantities {
+        let exponent = ((max_quantity - quantity) as u128).checked_mul(SCALE)? / liquidity as u128;
+        sum = sum.checked_add(exp_neg(exponent))?;
+    }
+    (max_quantity as u128)
+        .checked_mul(SCALE)?
+        .checked_add((liquidity as u128).checked_mul(ln(sum))?)
+}
+
+/// Cost of buying `amount` shares of `outcome` from an LMSR market maker,
+/// each share being worth `share_value` once resolved, rounded up.
+///
+/// Returns `None` on overflow or an invalid `outcome`.
+pub fn lmsr_buy_cost(
+    liquidity: u64,
+    quantities: &[u64],
+    outcome: usize,
+    amount: u64,
+    share_value: u64,
+) -> Option<u64> {
+    let mut new_quantities = quantities.to_vec();
+    let quantity = new_quantities.get_mut(outcome)?;
+    *quantity = quantity.checked_add(amount)?;
+
+    let cost = lmsr_cost(liquidity, &new_quantities)?
+        .saturating_sub(lmsr_cost(liquidity, quantities)?)
+        .checked_mul(share_value as u128)?;
+    let cost = cost / SCALE + u128::from(cost % SCALE != 0);
+    if cost > u64::MAX as u128 {
+        return None;
+    }
+    Some(cost as u64)
+}
+
+/// Proceeds of selling `amount` shares of `outcome` back to an LMSR market
+/// maker, each share being worth `share_value` once resolved, rounded down.
+///
+/// Returns `None` on overflow, an invalid `outcome` or when `amount` exceeds
+/// the outstanding quantity of `outcome`.
+pub fn lmsr_sell_proceeds(
+    liquidity: u64,
+    quantities: &[u64],
+    outcome: usize,
+    amount: u64,
+    share_value: u64,
+) -> Option<u64> {
+    let mut new_quantities = quantities.to_vec();
+    let quantity = new_quantities.get_mut(outcome)?;
+    *quantity = quantity.checked_sub(amount)?;
+
+    let proceeds = lmsr_cost(liquidity, quantities)?
+        .saturating_sub(lmsr_cost(liquidity, &new_quantities)?)
+        .checked_mul(share_value as u128)?
+        / SCALE;
+    if proceeds > u64::MAX as u128 {
+        return None;
+    }
+    Some(proceeds as u64)
+}
@@ -1,19 +1,36 @@
 //! Program state processor
 
 use crate::{
+    address::{
+        self, AUTHORITY_SEED, CONFIG_SEED, EVENT_SEED, FEE_VAULT_SEED, METADATA_SEED, MINT_SEED,
+        RESOLUTION_SEED, VAULT_SEED,
+    },
     error::PredictChainError,
-    instruction::PredictChainInstruction,
-    state::{EventAccount},
+    instruction::{ConfigArgs, CreateEventArgs, EventMetadataArgs, PredictChainInstruction},
+    math,
+    oracle::PriceFeed,
+    pricing,
+    state::{
+        Config, EventAccount, EventMetadata, EventStatus, EventType, Outcome, PricingMode,
+        Proposal, ProposalStatus, Resolution, ResolutionAccount, ResolverVote,
+    },
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::next_account_info,
     account_info::AccountInfo,
+    borsh::try_from_slice_unchecked,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     entrypoint::ProgramResult,
     msg,
-    program::{invoke_signed},
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
-    pubkey::Pubkey
+    program_pack::Pack,
+    pubkey::Pubkey,
+    clock::Clock,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::Sysvar,
 };
 
 /// Program state handler.
@@ -33,21 +50,198 @@ impl Processor {
                     program_id,
                     accounts,
                     args.side_index,
+                    args.num_tokens,
+                    args.max_cost
+                )
+            }
+            PredictChainInstruction::CreateEvent(args) => {
+                msg!("Instruction: CreateEvent");
+                Self::create_event(
+                    program_id,
+                    accounts,
+                    args
+                )
+            }
+            PredictChainInstruction::ResolveEvent(args) => {
+                msg!("Instruction: ResolveEvent");
+                Self::resolve_event(
+                    program_id,
+                    accounts,
+                    args.winning_side
+                )
+            }
+            PredictChainInstruction::RedeemShares(args) => {
+                msg!("Instruction: RedeemShares");
+                Self::redeem_shares(
+                    program_id,
+                    accounts,
+                    args.num_tokens
+                )
+            }
+            PredictChainInstruction::SellShares(args) => {
+                msg!("Instruction: SellShares");
+                Self::sell_shares(
+                    program_id,
+                    accounts,
+                    args.side_index,
+                    args.num_tokens,
+                    args.min_proceeds
+                )
+            }
+            PredictChainInstruction::MintCompleteSet(args) => {
+                msg!("Instruction: MintCompleteSet");
+                Self::mint_complete_set(
+                    program_id,
+                    accounts,
+                    args.num_sets
+                )
+            }
+            PredictChainInstruction::MergeCompleteSet(args) => {
+                msg!("Instruction: MergeCompleteSet");
+                Self::merge_complete_set(
+                    program_id,
+                    accounts,
+                    args.num_sets
+                )
+            }
+            PredictChainInstruction::ResolveScalarEvent(args) => {
+                msg!("Instruction: ResolveScalarEvent");
+                Self::resolve_scalar_event(
+                    program_id,
+                    accounts,
+                    args.value
+                )
+            }
+            PredictChainInstruction::VoidEvent => {
+                msg!("Instruction: VoidEvent");
+                Self::void_event(
+                    program_id,
+                    accounts
+                )
+            }
+            PredictChainInstruction::ClaimRefund(args) => {
+                msg!("Instruction: ClaimRefund");
+                Self::claim_refund(
+                    program_id,
+                    accounts,
                     args.num_tokens
                 )
             }
+            PredictChainInstruction::SetEventMetadata(args) => {
+                msg!("Instruction: SetEventMetadata");
+                Self::set_event_metadata(
+                    program_id,
+                    accounts,
+                    args
+                )
+            }
+            PredictChainInstruction::WithdrawFees => {
+                msg!("Instruction: WithdrawFees");
+                Self::withdraw_fees(
+                    program_id,
+                    accounts
+                )
+            }
+            PredictChainInstruction::InitializeConfig(args) => {
+                msg!("Instruction: InitializeConfig");
+                Self::initialize_config(
+                    program_id,
+                    accounts,
+                    args
+                )
+            }
+            PredictChainInstruction::UpdateConfig(args) => {
+                msg!("Instruction: UpdateConfig");
+                Self::update_config(
+                    program_id,
+                    accounts,
+                    args
+                )
+            }
+            PredictChainInstruction::SetPaused(args) => {
+                msg!("Instruction: SetPaused");
+                Self::set_paused(
+                    program_id,
+                    accounts,
+                    args.paused
+                )
+            }
+            PredictChainInstruction::ProposeResolveAuthority(args) => {
+                msg!("Instruction: ProposeResolveAuthority");
+                Self::propose_resolve_authority(
+                    program_id,
+                    accounts,
+                    args.new_resolve_authority
+                )
+            }
+            PredictChainInstruction::AcceptResolveAuthority => {
+                msg!("Instruction: AcceptResolveAuthority");
+                Self::accept_resolve_authority(
+                    program_id,
+                    accounts
+                )
+            }
+            PredictChainInstruction::VoteResolution(args) => {
+                msg!("Instruction: VoteResolution");
+                Self::vote_resolution(
+                    program_id,
+                    accounts,
+                    args.resolution
+                )
+            }
+            PredictChainInstruction::ProposeResolution(args) => {
+                msg!("Instruction: ProposeResolution");
+                Self::propose_resolution(
+                    program_id,
+                    accounts,
+                    args.resolution
+                )
+            }
+            PredictChainInstruction::DisputeResolution => {
+                msg!("Instruction: DisputeResolution");
+                Self::dispute_resolution(
+                    program_id,
+                    accounts
+                )
+            }
+            PredictChainInstruction::FinalizeResolution => {
+                msg!("Instruction: FinalizeResolution");
+                Self::finalize_resolution(
+                    program_id,
+                    accounts
+                )
+            }
+            PredictChainInstruction::SettleDispute(args) => {
+                msg!("Instruction: SettleDispute");
+                Self::settle_dispute(
+                    program_id,
+                    accounts,
+                    args.resolution
+                )
+            }
+            PredictChainInstruction::ResolveFromOracle => {
+                msg!("Instruction: ResolveFromOracle");
+                Self::resolve_from_oracle(
+                    program_id,
+                    accounts
+                )
+            }
         }
     }
 
 
-    /// Calculates the authority id by generating a program address.
+    /// Calculates the authority id from the canonical bump seed found by
+    /// `address::find_authority_address` at event creation.
     pub fn authority_id(
         program_id: &Pubkey,
-        seed: &Pubkey,
+        event: &Pubkey,
         bump_seed: u8,
     ) -> Result<Pubkey, ProgramError> {
-        Pubkey::create_program_address(&[&seed.to_bytes()[..32], &[bump_seed]], program_id)
-            .map_err(|_| PredictChainError::GenericError.into())
+        Pubkey::create_program_address(
+            &[AUTHORITY_SEED, &event.to_bytes(), &[bump_seed]],
+            program_id,
+        )
+        .map_err(|_| PredictChainError::InvalidAuthorityData.into())
     }
 
     /// Mint tokens
@@ -61,7 +255,7 @@ impl Processor {
         bump_seed: u8,
     ) -> ProgramResult {
         let me_bytes = event_pubkey.to_bytes();
-        let authority_signature_seeds = [&me_bytes[..32], &[bump_seed]];
+        let authority_signature_seeds = [AUTHORITY_SEED, &me_bytes[..32], &[bump_seed]];
         let signers = &[&authority_signature_seeds[..]];
 
         invoke_signed(
@@ -84,91 +278,2328 @@ impl Processor {
         )
     }
 
-    /// Purchase Shares instruction
-    pub fn purchase_shares(
+    /// Burn tokens
+    pub fn burn<'a>(
+        token_program_id: AccountInfo<'a>,
+        burn_account: AccountInfo<'a>,
+        mint_account: AccountInfo<'a>,
+        authority_account: AccountInfo<'a>,
+        amount: u64,
+        event_pubkey: &Pubkey,
+        bump_seed: u8,
+    ) -> ProgramResult {
+        let me_bytes = event_pubkey.to_bytes();
+        let authority_signature_seeds = [AUTHORITY_SEED, &me_bytes[..32], &[bump_seed]];
+        let signers = &[&authority_signature_seeds[..]];
+
+        invoke_signed(
+            &spl_token::instruction::burn(
+                token_program_id.key,
+                burn_account.key,
+                mint_account.key,
+                authority_account.key,
+                &[authority_account.key],
+                amount,
+            )?,
+            &[
+                token_program_id,
+                burn_account,
+                mint_account,
+                authority_account,
+            ],
+            signers,
+        )
+    }
+
+    /// Moves `cost` into the event pool: lamports transferred from the user by
+    /// the system program, or collateral tokens from the user's collateral
+    /// token account into the collateral vault for SPL collateral events
+    #[allow(clippy::too_many_arguments)]
+    pub fn collect_payment<'a>(
+        event: &AccountInfo<'a>,
+        event_data: &mut EventAccount,
+        user: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        collateral_vault: Option<&AccountInfo<'a>>,
+        user_collateral_account: Option<&AccountInfo<'a>>,
+        token_program_id: &AccountInfo<'a>,
+        cost: u64,
+    ) -> ProgramResult {
+        let volume = math::add(event_data.volume, cost)?;
+
+        let pool = if event_data.has_token_collateral() {
+            let collateral_vault = collateral_vault.ok_or(PredictChainError::InvalidCollateralAccount)?;
+            if *collateral_vault.key != event_data.collateral_vault {
+                return Err(PredictChainError::InvalidCollateralAccount.into());
+            }
+            collateral_vault
+        } else {
+            event
+        };
+        Self::deposit(
+            event_data,
+            user,
+            pool,
+            system_program,
+            user_collateral_account,
+            token_program_id,
+            cost,
+        )?;
+
+        // update volume in event account
+        event_data.volume = volume;
+
+        Ok(())
+    }
+
+    /// Moves the protocol and creator fees of a trade from the user into the
+    /// fee vault and adds them to the fees collected by the event
+    #[allow(clippy::too_many_arguments)]
+    pub fn collect_fees<'a>(
+        event_data: &mut EventAccount,
+        user: &AccountInfo<'a>,
+        fee_vault: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        user_collateral_account: Option<&AccountInfo<'a>>,
+        token_program_id: &AccountInfo<'a>,
+        protocol_fee: u64,
+        creator_fee: u64,
+    ) -> ProgramResult {
+        let fees = math::add(protocol_fee, creator_fee)?;
+        if fees > 0 {
+            Self::deposit(
+                event_data,
+                user,
+                fee_vault,
+                system_program,
+                user_collateral_account,
+                token_program_id,
+                fees,
+            )?;
+        }
+
+        event_data.protocol_fees = math::add(event_data.protocol_fees, protocol_fee)?;
+        event_data.creator_fees = math::add(event_data.creator_fees, creator_fee)?;
+
+        Ok(())
+    }
+
+    /// Transfers `amount` from the user to `destination`: lamports by the
+    /// system program, or collateral tokens from the user's collateral token
+    /// account for SPL collateral events
+    fn deposit<'a>(
+        event_data: &EventAccount,
+        user: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        user_collateral_account: Option<&AccountInfo<'a>>,
+        token_program_id: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        if event_data.has_token_collateral() {
+            let user_collateral_account =
+                user_collateral_account.ok_or(PredictChainError::InvalidCollateralAccount)?;
+            Self::unpack_token_account(user_collateral_account, &event_data.collateral_mint)
+                .map_err(|_| PredictChainError::InvalidCollateralAccount)?;
+
+            Self::transfer(
+                token_program_id.clone(),
+                user_collateral_account.clone(),
+                destination.clone(),
+                user.clone(),
+                amount,
+                &[],
+            )
+        } else {
+            if *system_program.key != system_program::id() {
+                return Err(PredictChainError::InvalidSystemProgram.into());
+            }
+
+            invoke(
+                &system_instruction::transfer(user.key, destination.key, amount),
+                &[user.clone(), destination.clone(), system_program.clone()],
+            )
+        }
+    }
+
+    /// Pays `amount` out of the event pool: lamports from the event account,
+    /// which must stay rent exempt, or collateral tokens from the collateral
+    /// vault for SPL collateral events
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_out<'a>(
+        event: &AccountInfo<'a>,
+        event_data: &mut EventAccount,
+        authority: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        collateral_vault: Option<&AccountInfo<'a>>,
+        token_program_id: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        let volume = event_data
+            .volume
+            .checked_sub(amount)
+            .ok_or(PredictChainError::InsufficientFunds)?;
+
+        if event_data.has_token_collateral() {
+            let collateral_vault = collateral_vault.ok_or(PredictChainError::InvalidCollateralAccount)?;
+            if *collateral_vault.key != event_data.collateral_vault {
+                return Err(PredictChainError::InvalidCollateralAccount.into());
+            }
+            Self::unpack_token_account(destination, &event_data.collateral_mint)
+                .map_err(|_| PredictChainError::InvalidCollateralAccount)?;
+
+            let me_bytes = event.key.to_bytes();
+            let authority_signature_seeds =
+                [AUTHORITY_SEED, &me_bytes[..32], &[event_data.bump_seed]];
+            Self::transfer(
+                token_program_id.clone(),
+                collateral_vault.clone(),
+                destination.clone(),
+                authority.clone(),
+                amount,
+                &[&authority_signature_seeds[..]],
+            )?;
+        } else {
+            let rent_exemption = Rent::get()?.minimum_balance(event.data_len());
+            if event.lamports() < math::add(rent_exemption, amount)? {
+                return Err(PredictChainError::InsufficientFunds.into());
+            }
+
+            // transfer solana to destination
+            let event_lamports = math::sub(event.lamports(), amount)?;
+            let destination_lamports = math::add(destination.lamports(), amount)?;
+            **event.try_borrow_mut_lamports()? = event_lamports;
+            **destination.try_borrow_mut_lamports()? = destination_lamports;
+        }
+
+        // update volume in event account
+        event_data.volume = volume;
+
+        Ok(())
+    }
+
+    /// Loads the program config, checking the address of its account
+    fn load_config(program_id: &Pubkey, config: &AccountInfo) -> Result<Config, ProgramError> {
+        let (config_address, _) = address::find_config_address(program_id);
+        if *config.key != config_address {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        if !config.data_is_empty() && config.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        Config::decode(&config.data.borrow())
+    }
+
+    /// Loads the program config, failing while the program is paused
+    fn unpaused_config(program_id: &Pubkey, config: &AccountInfo) -> Result<Config, ProgramError> {
+        let config_data = Self::load_config(program_id, config)?;
+        if config_data.paused {
+            return Err(PredictChainError::ProgramPaused.into());
+        }
+        Ok(config_data)
+    }
+
+    /// Loads an event, checking that it lives at the address derived from its
+    /// creator and event id so that no other account of the program passes
+    /// for it
+    fn load_event(program_id: &Pubkey, event: &AccountInfo) -> Result<EventAccount, ProgramError> {
+        let event_data = EventAccount::try_from_slice(&event.data.borrow())?;
+        let event_address = Pubkey::create_program_address(
+            &[
+                EVENT_SEED,
+                event_data.creator.as_ref(),
+                &event_data.event_id.to_le_bytes(),
+                &[event_data.event_bump_seed],
+            ],
+            program_id,
+        )
+        .map_err(|_| PredictChainError::WrongAccountAddress)?;
+        if *event.key != event_address {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        Ok(event_data)
+    }
+
+    /// Checks that an account is the SPL Token program
+    fn check_token_program(token_program_id: &AccountInfo) -> ProgramResult {
+        if *token_program_id.key != spl_token::id() {
+            return Err(PredictChainError::InvalidTokenProgram.into());
+        }
+        Ok(())
+    }
+
+    /// Unpacks a token account, checking it is owned by the token program and
+    /// holds tokens of `mint`
+    fn unpack_token_account(
+        token_account: &AccountInfo,
+        mint: &Pubkey,
+    ) -> Result<spl_token::state::Account, ProgramError> {
+        if *token_account.owner != spl_token::id() {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        let token_account_data = spl_token::state::Account::unpack(&token_account.data.borrow())?;
+        if token_account_data.mint != *mint {
+            return Err(PredictChainError::InvalidTokenMint.into());
+        }
+        Ok(token_account_data)
+    }
+
+    /// Transfer tokens
+    pub fn transfer<'a>(
+        token_program_id: AccountInfo<'a>,
+        source: AccountInfo<'a>,
+        destination: AccountInfo<'a>,
+        authority_account: AccountInfo<'a>,
+        amount: u64,
+        signers: &[&[&[u8]]],
+    ) -> ProgramResult {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_id.key,
+                source.key,
+                destination.key,
+                authority_account.key,
+                &[],
+                amount,
+            )?,
+            &[source, destination, authority_account, token_program_id],
+            signers,
+        )
+    }
+
+    /// Initialize a mint with the event authority as mint authority
+    pub fn initialize_mint<'a>(
+        token_program_id: AccountInfo<'a>,
+        mint_account: AccountInfo<'a>,
+        rent_info: AccountInfo<'a>,
+        authority: &Pubkey,
+    ) -> ProgramResult {
+        invoke(
+            &spl_token::instruction::initialize_mint(
+                token_program_id.key,
+                mint_account.key,
+                authority,
+                None,
+                0,
+            )?,
+            &[mint_account, rent_info, token_program_id],
+        )
+    }
+
+    /// Initialize a token account of `mint` owned by the event authority
+    pub fn initialize_token_account<'a>(
+        token_program_id: AccountInfo<'a>,
+        account: AccountInfo<'a>,
+        mint: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        rent_info: AccountInfo<'a>,
+    ) -> ProgramResult {
+        invoke(
+            &spl_token::instruction::initialize_account(
+                token_program_id.key,
+                account.key,
+                mint.key,
+                authority.key,
+            )?,
+            &[account, mint, authority, rent_info, token_program_id],
+        )
+    }
+
+    /// Allocates `space` bytes of the program derived account signed for by
+    /// `signer_seeds` and assigns it to `owner`, unless it already is, funding
+    /// it from `payer` on top of any lamports already sent to its address
+    fn create_derived_account<'a>(
+        payer: &AccountInfo<'a>,
+        account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        rent: &Rent,
+        signer_seeds: &[&[u8]],
+        space: usize,
+        owner: &Pubkey,
+    ) -> ProgramResult {
+        let required_lamports = rent
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, required_lamports),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        if *account.owner == system_program::id() {
+            invoke_signed(
+                &system_instruction::allocate(account.key, space as u64),
+                &[account.clone(), system_program.clone()],
+                &[signer_seeds],
+            )?;
+            invoke_signed(
+                &system_instruction::assign(account.key, owner),
+                &[account.clone(), system_program.clone()],
+                &[signer_seeds],
+            )?;
+        }
+
+        if account.owner != owner {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if account.data_len() != space {
+            return Err(PredictChainError::InvalidAccountSize.into());
+        }
+        Ok(())
+    }
+
+    /// Create Event instruction
+    pub fn create_event(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        side_index: u8,
-        num_tokens: u64
+        args: CreateEventArgs
     ) -> ProgramResult {
         let account_iter = &mut accounts.iter();
-        let user_signer = next_account_info(account_iter)?;
+        let creator = next_account_info(account_iter)?;
         let authority = next_account_info(account_iter)?;
         let event = next_account_info(account_iter)?;
-        let yes_mint = next_account_info(account_iter)?;
-        let no_mint = next_account_info(account_iter)?;
-        let user_token_account = next_account_info(account_iter)?;
-        let payment_account = next_account_info(account_iter)?;
+        let metadata = next_account_info(account_iter)?;
+        let outcome_mints = args
+            .outcome_labels
+            .iter()
+            .map(|_| next_account_info(account_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let rent_info = next_account_info(account_iter)?;
         let token_program_id = next_account_info(account_iter)?;
+        let system_program = next_account_info(account_iter)?;
+        let fee_vault = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+        let resolution = if args.resolvers.is_empty() {
+            None
+        } else {
+            Some(next_account_info(account_iter)?)
+        };
+        let collateral_mint = account_iter.next();
+        let collateral_vault = account_iter.next();
 
-        // side index must be in [0, 1]
-        if side_index > 1 {
-            return Err(PredictChainError::GenericError.into());
-        }
+        let config_data = Self::unpaused_config(program_id, config)?;
 
-        // num tokens must be > 0
-        if num_tokens == 0 {
-            return Err(PredictChainError::GenericError.into());
+        if !creator.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        Self::check_token_program(token_program_id)?;
+        if *system_program.key != system_program::id() {
+            return Err(PredictChainError::InvalidSystemProgram.into());
         }
 
-        if event.owner != program_id {
-            // msg!("event account isn't owned by program");
-            return Err(PredictChainError::GenericError.into());
+        // between two and MAX_OUTCOMES labelled outcomes, each with its own mint
+        let num_outcomes = args.outcome_labels.len();
+        if !(2..=EventAccount::MAX_OUTCOMES).contains(&num_outcomes) {
+            return Err(PredictChainError::InvalidOutcomes.into());
         }
-        if payment_account.owner != program_id {
-            // msg!("payment account isn't owned by program");
-            return Err(PredictChainError::GenericError.into());
+        if args
+            .outcome_labels
+            .iter()
+            .any(|label| label.is_empty() || label.len() > EventAccount::MAX_LABEL_LEN)
+        {
+            return Err(PredictChainError::InvalidOutcomes.into());
         }
-        if !user_signer.is_signer {
-            // msg!("user should be signer");
-            return Err(PredictChainError::GenericError.into());
+
+        // the event lives at the address derived from the creator and the
+        // event id, its other accounts at addresses derived from the event
+        let (event_address, event_bump_seed) =
+            address::find_event_address(program_id, creator.key, args.event_id);
+        let (metadata_address, metadata_bump_seed) =
+            address::find_metadata_address(program_id, event.key);
+        let (fee_vault_address, fee_vault_bump_seed) =
+            address::find_fee_vault_address(program_id, event.key);
+        if *event.key != event_address
+            || *metadata.key != metadata_address
+            || *fee_vault.key != fee_vault_address
+        {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        let mint_bump_seeds = outcome_mints
+            .iter()
+            .enumerate()
+            .map(|(index, mint)| {
+                let (mint_address, bump_seed) =
+                    address::find_mint_address(program_id, event.key, index as u8);
+                if *mint.key != mint_address {
+                    return Err(PredictChainError::InvalidTokenMint);
+                }
+                Ok(bump_seed)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // scalar events have a LONG and a SHORT outcome over a non-empty range
+        if args.event_type == EventType::Scalar {
+            if num_outcomes != 2 {
+                return Err(PredictChainError::InvalidOutcomes.into());
+            }
+            if args.lower_bound >= args.upper_bound {
+                return Err(PredictChainError::InvalidBounds.into());
+            }
         }
 
-        let mint_to_use = if side_index == 0 { yes_mint } else { no_mint };
+        // trading window must be non-empty
+        if args.open_time >= args.close_time {
+            return Err(PredictChainError::InvalidBounds.into());
+        }
 
-        // msg!("129");
-        // msg!("{}", event.key);
-        let mut event_data = EventAccount::try_from_slice(&event.data.borrow())?;
-        msg!("131");
+        // proposals are bonded and can't be combined with a resolver set
+        if args.dispute_window < 0 || (args.dispute_window > 0 && !args.resolvers.is_empty()) {
+            return Err(PredictChainError::InvalidProposal.into());
+        }
+        if args.dispute_window > 0 && args.proposal_bond == 0 {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
 
-        msg!("event pubkey: {}", event.key);        
+        // oracle events resolve themselves, binary ones against the strike,
+        // from a price account of the oracle program of the config
+        let oracle_program = if args.oracle != Pubkey::default() {
+            if !args.resolvers.is_empty() || args.dispute_window > 0 || args.max_staleness < 0 {
+                return Err(PredictChainError::InvalidOracle.into());
+            }
+            if config_data.oracle_program == Pubkey::default() {
+                return Err(PredictChainError::InvalidOracle.into());
+            }
+            if num_outcomes != 2 {
+                return Err(PredictChainError::InvalidOutcomes.into());
+            }
+            config_data.oracle_program
+        } else {
+            Pubkey::default()
+        };
 
-        let authority_pub_key = Self::authority_id(program_id, event.key, event_data.bump_seed)?;
-        msg!("135");
-        msg!("bumpseed: {}", event_data.bump_seed);
-        msg!("received authority: {}", *authority.key);
-        msg!("calculated authority: {}", authority_pub_key);
-        if *authority.key != authority_pub_key {
-            msg!("136");
-            return Err(PredictChainError::GenericError.into());
+        if args.price_per_share == 0 {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
+        if config_data
+            .protocol_fee_bps
+            .checked_add(args.creator_fee_bps)
+            .filter(|fee_bps| *fee_bps <= EventAccount::MAX_FEE_BPS)
+            .is_none()
+        {
+            return Err(PredictChainError::InvalidFee.into());
         }
 
-        msg!("140");
+        // market maker reserves start balanced, valuing each side at one half
+        let reserve = match args.pricing_mode {
+            PricingMode::FixedPrice => 0,
+            PricingMode::Lmsr => {
+                if num_outcomes > EventAccount::MAX_LMSR_OUTCOMES {
+                    return Err(PredictChainError::InvalidOutcomes.into());
+                }
+                if args.liquidity == 0 {
+                    return Err(PredictChainError::InvalidAmount.into());
+                }
+                0
+            }
+            PricingMode::ConstantProduct => {
+                // the constant-product market maker only prices binary events
+                if num_outcomes != 2 {
+                    return Err(PredictChainError::InvalidOutcomes.into());
+                }
+                args.liquidity
+                    .checked_mul(args.price_per_share)
+                    .filter(|reserve| *reserve > 0)
+                    .ok_or(PredictChainError::InvalidAmount)?
+            }
+        };
 
-        // mint tokens to user account
-        Self::mint(
-            token_program_id.clone(),
-            mint_to_use.clone(),
-            user_token_account.clone(),
-            authority.clone(),
-            num_tokens,
-            event.key,
-            event_data.bump_seed,
+        let rent = &Rent::from_account_info(rent_info)?;
+        let event_id_bytes = args.event_id.to_le_bytes();
+        Self::create_derived_account(
+            creator,
+            event,
+            system_program,
+            rent,
+            &[EVENT_SEED, creator.key.as_ref(), &event_id_bytes, &[event_bump_seed]],
+            EventAccount::packed_len(&args.outcome_labels),
+            program_id,
         )?;
+        let event_data = try_from_slice_unchecked::<EventAccount>(&event.data.borrow())?;
+        if event_data.is_initialized() {
+            return Err(PredictChainError::AlreadyInUse.into());
+        }
 
-        msg!("152");
+        Self::create_derived_account(
+            creator,
+            metadata,
+            system_program,
+            rent,
+            &[METADATA_SEED, event.key.as_ref(), &[metadata_bump_seed]],
+            EventMetadata::LEN,
+            program_id,
+        )?;
+        if EventMetadata::decode(&metadata.data.borrow())?.is_initialized() {
+            return Err(PredictChainError::AlreadyInUse.into());
+        }
+        Self::write_metadata(metadata, event.key, args.metadata)?;
 
-        // update volume in event account
-        event_data.volume += **payment_account.lamports.borrow();
-        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+        // a resolver set decides the event once `resolution_threshold` of its
+        // distinct members vote the same way
+        if let Some(resolution) = resolution {
+            let num_resolvers = args.resolvers.len();
+            if num_resolvers > ResolutionAccount::MAX_RESOLVERS
+                || !(1..=num_resolvers).contains(&(args.resolution_threshold as usize))
+            {
+                return Err(PredictChainError::InvalidResolvers.into());
+            }
+            for (index, resolver) in args.resolvers.iter().enumerate() {
+                if args.resolvers[..index].contains(resolver) {
+                    return Err(PredictChainError::InvalidResolvers.into());
+                }
+            }
 
-        msg!("158");
+            let (resolution_address, resolution_bump_seed) =
+                address::find_resolution_address(program_id, event.key);
+            if *resolution.key != resolution_address {
+                return Err(PredictChainError::WrongAccountAddress.into());
+            }
+            Self::create_derived_account(
+                creator,
+                resolution,
+                system_program,
+                rent,
+                &[RESOLUTION_SEED, event.key.as_ref(), &[resolution_bump_seed]],
+                ResolutionAccount::packed_len(num_resolvers),
+                program_id,
+            )?;
+            if ResolutionAccount::decode(&resolution.data.borrow())?.is_initialized() {
+                return Err(PredictChainError::AlreadyInUse.into());
+            }
 
-        // transfer solana to event account
-        **event.try_borrow_mut_lamports()? += **payment_account.lamports.borrow();
-        **payment_account.try_borrow_mut_lamports()? = 0;
+            let resolution_data = ResolutionAccount {
+                event: *event.key,
+                threshold: args.resolution_threshold,
+                votes: args
+                    .resolvers
+                    .iter()
+                    .map(|resolver| ResolverVote {
+                        resolver: *resolver,
+                        vote: None,
+                    })
+                    .collect(),
+            };
+            resolution_data.serialize(&mut &mut resolution.data.borrow_mut()[..])?;
+        }
+
+        let (authority_pub_key, bump_seed) = address::find_authority_address(program_id, event.key);
+        if *authority.key != authority_pub_key {
+            return Err(PredictChainError::InvalidAuthorityAccount.into());
+        }
+
+        // all mints are controlled by the event authority
+        for (index, (&mint_account, mint_bump_seed)) in
+            outcome_mints.iter().zip(mint_bump_seeds).enumerate()
+        {
+            Self::create_derived_account(
+                creator,
+                mint_account,
+                system_program,
+                rent,
+                &[MINT_SEED, event.key.as_ref(), &[index as u8], &[mint_bump_seed]],
+                spl_token::state::Mint::LEN,
+                token_program_id.key,
+            )?;
+            Self::initialize_mint(
+                token_program_id.clone(),
+                mint_account.clone(),
+                rent_info.clone(),
+                authority.key,
+            )?;
+        }
+
+        // SPL collateral and fees are held in vaults owned by the event
+        // authority, lamport fees in a data-less account of the program
+        let collateral = (collateral_mint, collateral_vault);
+        let (collateral_mint_address, collateral_vault_address) = match collateral {
+            (Some(collateral_mint), Some(collateral_vault)) => {
+                if collateral_mint.owner != token_program_id.key {
+                    return Err(PredictChainError::InvalidTokenMint.into());
+                }
+                let (vault_address, vault_bump_seed) =
+                    address::find_vault_address(program_id, event.key);
+                if *collateral_vault.key != vault_address {
+                    return Err(PredictChainError::WrongAccountAddress.into());
+                }
+                let vaults = [
+                    (collateral_vault, VAULT_SEED, vault_bump_seed),
+                    (fee_vault, FEE_VAULT_SEED, fee_vault_bump_seed),
+                ];
+                for (vault, seed, bump_seed) in vaults {
+                    Self::create_derived_account(
+                        creator,
+                        vault,
+                        system_program,
+                        rent,
+                        &[seed, event.key.as_ref(), &[bump_seed]],
+                        spl_token::state::Account::LEN,
+                        token_program_id.key,
+                    )?;
+                    Self::initialize_token_account(
+                        token_program_id.clone(),
+                        vault.clone(),
+                        collateral_mint.clone(),
+                        authority.clone(),
+                        rent_info.clone(),
+                    )?;
+                }
+                (*collateral_mint.key, *collateral_vault.key)
+            }
+            (None, None) => {
+                Self::create_derived_account(
+                    creator,
+                    fee_vault,
+                    system_program,
+                    rent,
+                    &[FEE_VAULT_SEED, event.key.as_ref(), &[fee_vault_bump_seed]],
+                    0,
+                    program_id,
+                )?;
+                (Pubkey::default(), Pubkey::default())
+            }
+            _ => return Err(PredictChainError::InvalidCollateralAccount.into()),
+        };
+
+        let outcomes = outcome_mints
+            .iter()
+            .zip(args.outcome_labels)
+            .map(|(mint, label)| Outcome {
+                mint_address: *mint.key,
+                label,
+                reserve,
+                quantity: 0,
+                payout_pool: 0,
+                collateral: 0,
+                supply: 0,
+            })
+            .collect();
+
+        let event_data = EventAccount {
+            bump_seed,
+            event_bump_seed,
+            resolve_authority: args.resolve_authority,
+            pending_resolve_authority: Pubkey::default(),
+            resolution: resolution.map_or_else(Pubkey::default, |resolution| *resolution.key),
+            dispute_window: args.dispute_window,
+            proposal_bond: args.proposal_bond,
+            proposal: Proposal::none(),
+            oracle: args.oracle,
+            oracle_program,
+            strike: args.strike,
+            price_expo: args.price_expo,
+            max_staleness: args.max_staleness,
+            max_confidence_bps: args.max_confidence_bps,
+            creator: *creator.key,
+            event_id: args.event_id,
+            metadata: *metadata.key,
+            volume: 0,
+            protocol_fees: 0,
+            creator_fees: 0,
+            protocol_fee_bps: config_data.protocol_fee_bps,
+            creator_fee_bps: args.creator_fee_bps,
+            protocol_fee_recipient: config_data.protocol_fee_recipient,
+            fee_vault: *fee_vault.key,
+            price_per_share: args.price_per_share,
+            pricing_mode: args.pricing_mode,
+            liquidity: args.liquidity,
+            event_type: args.event_type,
+            lower_bound: args.lower_bound,
+            upper_bound: args.upper_bound,
+            open_time: args.open_time,
+            close_time: args.close_time,
+            status: EventStatus::Open,
+            winning_side: 0,
+            resolved_value: 0,
+            resolved_at: 0,
+            collateral_mint: collateral_mint_address,
+            collateral_vault: collateral_vault_address,
+            outcomes,
+        };
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Writes the metadata of an event over the whole metadata account
+    fn write_metadata(
+        metadata: &AccountInfo,
+        event: &Pubkey,
+        args: EventMetadataArgs,
+    ) -> ProgramResult {
+        let metadata_data = EventMetadata {
+            event: *event,
+            title: args.title,
+            description: args.description,
+            league: args.league,
+            category: args.category,
+            image0: args.image0,
+            image1: args.image1,
+        };
+        if !metadata_data.is_valid() {
+            return Err(PredictChainError::InvalidMetadata.into());
+        }
+
+        let mut data = metadata.data.borrow_mut();
+        data.fill(0);
+        metadata_data.serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+
+    /// Set Event Metadata instruction
+    pub fn set_event_metadata(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        args: EventMetadataArgs
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let creator = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let metadata = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id || metadata.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !creator.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let event_data = Self::load_event(program_id, event)?;
+        if *creator.key != event_data.creator {
+            return Err(PredictChainError::WrongCreator.into());
+        }
+        if *metadata.key != event_data.metadata
+            || *metadata.key != address::find_metadata_address(program_id, event.key).0
+            || EventMetadata::decode(&metadata.data.borrow())?.event != *event.key
+        {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+
+        // metadata is frozen once trading opens
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::EventClosed.into());
+        }
+        if Clock::get()?.unix_timestamp >= event_data.open_time {
+            return Err(PredictChainError::MetadataLocked.into());
+        }
+
+        Self::write_metadata(metadata, event.key, args)
+    }
+
+    /// Computes the cost in lamports of buying `num_tokens` of an outcome and
+    /// updates the market maker state accordingly
+    pub fn purchase_cost(
+        event_data: &mut EventAccount,
+        side_index: u8,
+        num_tokens: u64,
+    ) -> Result<u64, ProgramError> {
+        match event_data.pricing_mode {
+            PricingMode::FixedPrice => Ok(math::mul(num_tokens, event_data.price_per_share)?),
+            PricingMode::ConstantProduct => {
+                let amount = math::mul(num_tokens, event_data.price_per_share)?;
+                let (outcome_in, outcome_out) = Self::binary_outcomes(event_data, side_index)?;
+                let cost =
+                    pricing::constant_product_buy_cost(outcome_in.reserve, outcome_out.reserve, amount)
+                        .ok_or(PredictChainError::InsufficientLiquidity)?;
+
+                // cost is added to both reserves and the shares leave their side
+                outcome_in.reserve = outcome_in
+                    .reserve
+                    .checked_add(cost)
+                    .and_then(|reserve| reserve.checked_sub(amount))
+                    .filter(|reserve| *reserve > 0)
+                    .ok_or(PredictChainError::InsufficientLiquidity)?;
+                outcome_out.reserve = math::add(outcome_out.reserve, cost)?;
+                Ok(cost)
+            }
+            PricingMode::Lmsr => {
+                let cost = pricing::lmsr_buy_cost(
+                    event_data.liquidity,
+                    &event_data.quantities(),
+                    side_index as usize,
+                    num_tokens,
+                    event_data.price_per_share,
+                )
+                .ok_or(PredictChainError::InsufficientLiquidity)?;
+
+                let outcome = &mut event_data.outcomes[side_index as usize];
+                outcome.quantity = math::add(outcome.quantity, num_tokens)?;
+                Ok(cost)
+            }
+        }
+    }
+
+    /// Computes the proceeds in lamports of selling `num_tokens` of an outcome
+    /// and updates the market maker state accordingly
+    pub fn sale_proceeds(
+        event_data: &mut EventAccount,
+        side_index: u8,
+        num_tokens: u64,
+    ) -> Result<u64, ProgramError> {
+        match event_data.pricing_mode {
+            PricingMode::FixedPrice => Ok(math::mul(num_tokens, event_data.price_per_share)?),
+            PricingMode::ConstantProduct => {
+                let amount = math::mul(num_tokens, event_data.price_per_share)?;
+                let (outcome_in, outcome_out) = Self::binary_outcomes(event_data, side_index)?;
+                let proceeds =
+                    pricing::constant_product_sell_proceeds(outcome_in.reserve, outcome_out.reserve, amount)
+                        .ok_or(PredictChainError::InsufficientLiquidity)?;
+
+                // shares join their side and the proceeds leave both reserves
+                outcome_in.reserve = outcome_in
+                    .reserve
+                    .checked_add(amount)
+                    .and_then(|reserve| reserve.checked_sub(proceeds))
+                    .ok_or(PredictChainError::InsufficientLiquidity)?;
+                outcome_out.reserve = outcome_out
+                    .reserve
+                    .checked_sub(proceeds)
+                    .filter(|reserve| *reserve > 0)
+                    .ok_or(PredictChainError::InsufficientLiquidity)?;
+                Ok(proceeds)
+            }
+            PricingMode::Lmsr => {
+                let proceeds = pricing::lmsr_sell_proceeds(
+                    event_data.liquidity,
+                    &event_data.quantities(),
+                    side_index as usize,
+                    num_tokens,
+                    event_data.price_per_share,
+                )
+                .ok_or(PredictChainError::InsufficientLiquidity)?;
+
+                let outcome = &mut event_data.outcomes[side_index as usize];
+                outcome.quantity = math::sub(outcome.quantity, num_tokens)?;
+                Ok(proceeds)
+            }
+        }
+    }
+
+    /// Splits the outcomes of a binary event into the traded outcome and the
+    /// other one
+    fn binary_outcomes(
+        event_data: &mut EventAccount,
+        side_index: u8,
+    ) -> Result<(&mut Outcome, &mut Outcome), ProgramError> {
+        match (side_index, &mut event_data.outcomes[..]) {
+            (0, [first, second]) => Ok((first, second)),
+            (1, [first, second]) => Ok((second, first)),
+            _ => Err(PredictChainError::InvalidSide.into()),
+        }
+    }
+
+    /// Reads the mints of all outcomes of an event, followed by one token
+    /// account per outcome
+    #[allow(clippy::type_complexity)]
+    fn outcome_accounts<'a, 'b>(
+        account_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        event_data: &EventAccount,
+    ) -> Result<(Vec<&'a AccountInfo<'b>>, Vec<&'a AccountInfo<'b>>), ProgramError> {
+        let mut outcome_mints = Vec::with_capacity(event_data.outcomes.len());
+        for outcome in event_data.outcomes.iter() {
+            let mint = next_account_info(account_iter)?;
+            if *mint.key != outcome.mint_address {
+                return Err(PredictChainError::InvalidTokenMint.into());
+            }
+            outcome_mints.push(mint);
+        }
+        let user_token_accounts = event_data
+            .outcomes
+            .iter()
+            .map(|_| next_account_info(account_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((outcome_mints, user_token_accounts))
+    }
+
+    /// Purchase Shares instruction
+    pub fn purchase_shares(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        side_index: u8,
+        num_tokens: u64,
+        max_cost: u64
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let user_signer = next_account_info(account_iter)?;
+        let authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let mint = next_account_info(account_iter)?;
+        let user_token_account = next_account_info(account_iter)?;
+        let system_program = next_account_info(account_iter)?;
+        let token_program_id = next_account_info(account_iter)?;
+        let fee_vault = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+        let collateral_vault = account_iter.next();
+        let user_collateral_account = account_iter.next();
+
+        Self::unpaused_config(program_id, config)?;
+
+        // num tokens must be > 0
+        if num_tokens == 0 {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !user_signer.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        Self::check_token_program(token_program_id)?;
+
+        let mut event_data = Self::load_event(program_id, event)?;
+
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::EventClosed.into());
+        }
+        if !event_data.is_trading_open(Clock::get()?.unix_timestamp) {
+            return Err(PredictChainError::OutsideTradingWindow.into());
+        }
+        if *fee_vault.key != event_data.fee_vault {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+
+        // side index must be a valid outcome
+        let outcome = event_data
+            .outcomes
+            .get(side_index as usize)
+            .ok_or(PredictChainError::InvalidSide)?;
+        if *mint.key != outcome.mint_address {
+            return Err(PredictChainError::InvalidTokenMint.into());
+        }
+        Self::unpack_token_account(user_token_account, mint.key)?;
+
+        let authority_pub_key = Self::authority_id(program_id, event.key, event_data.bump_seed)?;
+        if *authority.key != authority_pub_key {
+            return Err(PredictChainError::InvalidAuthorityAccount.into());
+        }
+
+        let cost = Self::purchase_cost(&mut event_data, side_index, num_tokens)?;
+        let (protocol_fee, creator_fee) = event_data.trading_fees(cost)?;
+        let total_cost = math::add(math::add(cost, protocol_fee)?, creator_fee)?;
+        if total_cost > max_cost {
+            msg!("cost of {} exceeds maximum of {}", total_cost, max_cost);
+            return Err(PredictChainError::SlippageExceeded.into());
+        }
+        let outcome = &mut event_data.outcomes[side_index as usize];
+        outcome.collateral = math::add(outcome.collateral, cost)?;
+        outcome.supply = math::add(outcome.supply, num_tokens)?;
+
+        Self::collect_payment(
+            event,
+            &mut event_data,
+            user_signer,
+            system_program,
+            collateral_vault,
+            user_collateral_account,
+            token_program_id,
+            cost,
+        )?;
+        Self::collect_fees(
+            &mut event_data,
+            user_signer,
+            fee_vault,
+            system_program,
+            user_collateral_account,
+            token_program_id,
+            protocol_fee,
+            creator_fee,
+        )?;
+
+        // mint tokens to user account
+        Self::mint(
+            token_program_id.clone(),
+            mint.clone(),
+            user_token_account.clone(),
+            authority.clone(),
+            num_tokens,
+            event.key,
+            event_data.bump_seed,
+        )?;
+
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Resolve Event instruction
+    pub fn resolve_event(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        winning_side: u8
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let resolve_authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !resolve_authority.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        let resolution = Resolution::Side(winning_side);
+        Self::check_resolve_authority(&event_data, resolve_authority, resolution)?;
+
+        Self::settle(&mut event_data, resolution)?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Resolve Scalar Event instruction
+    pub fn resolve_scalar_event(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        value: i64
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let resolve_authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !resolve_authority.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        let resolution = Resolution::Value(value);
+        Self::check_resolve_authority(&event_data, resolve_authority, resolution)?;
+
+        Self::settle(&mut event_data, resolution)?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Void Event instruction
+    pub fn void_event(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let resolve_authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !resolve_authority.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        let resolution = Resolution::Void;
+        Self::check_resolve_authority(&event_data, resolve_authority, resolution)?;
+
+        Self::settle(&mut event_data, resolution)?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Checks that the resolve authority of an event decides it alone, oracle
+    /// and optimistic events only being voided by it
+    fn check_resolve_authority(
+        event_data: &EventAccount,
+        resolve_authority: &AccountInfo,
+        resolution: Resolution,
+    ) -> ProgramResult {
+        if *resolve_authority.key != event_data.resolve_authority {
+            return Err(PredictChainError::WrongResolveAuthority.into());
+        }
+        if event_data.has_resolver_set() {
+            return Err(PredictChainError::ResolverVoteRequired.into());
+        }
+        // optimistic events can only be voided before a resolution is proposed
+        if event_data.is_optimistic()
+            && (resolution != Resolution::Void || event_data.proposal.status != ProposalStatus::None)
+        {
+            return Err(PredictChainError::ProposalRequired.into());
+        }
+        if event_data.has_oracle() && resolution != Resolution::Void {
+            return Err(PredictChainError::OracleRequired.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that an open event can be settled by `resolution`
+    fn check_resolution(event_data: &EventAccount, resolution: Resolution) -> ProgramResult {
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::DecisionAlreadyMade.into());
+        }
+        match resolution {
+            Resolution::Side(winning_side) => {
+                if event_data.event_type != EventType::Categorical {
+                    return Err(PredictChainError::WrongEventType.into());
+                }
+                // winning side must be a valid outcome
+                if winning_side as usize >= event_data.outcomes.len() {
+                    return Err(PredictChainError::InvalidSide.into());
+                }
+            }
+            Resolution::Value(_) => {
+                if event_data.event_type != EventType::Scalar {
+                    return Err(PredictChainError::WrongEventType.into());
+                }
+            }
+            Resolution::Void => {}
+        }
+        Ok(())
+    }
+
+    /// Settles an open event, setting aside the payout pool of each outcome
+    fn settle(event_data: &mut EventAccount, resolution: Resolution) -> ProgramResult {
+        Self::check_resolution(event_data, resolution)?;
+
+        match resolution {
+            Resolution::Side(winning_side) => {
+                // winners take the whole pool
+                event_data.outcomes[winning_side as usize].payout_pool = event_data.volume;
+                event_data.status = EventStatus::Resolved;
+                event_data.winning_side = winning_side;
+            }
+            Resolution::Value(value) => {
+                // LONG gets the part of the pool the value covers between the bounds
+                let value = value.clamp(event_data.lower_bound, event_data.upper_bound);
+                let covered = (value as i128 - event_data.lower_bound as i128) as u128;
+                let range = (event_data.upper_bound as i128 - event_data.lower_bound as i128) as u128;
+                let long_pool = math::mul_div(event_data.volume.into(), covered, range)?;
+                event_data.outcomes[0].payout_pool = long_pool;
+                event_data.outcomes[1].payout_pool = math::sub(event_data.volume, long_pool)?;
+                event_data.status = EventStatus::Resolved;
+                event_data.resolved_value = value;
+            }
+            Resolution::Void => {
+                Self::refund_pools(event_data)?;
+                event_data.status = EventStatus::Voided;
+            }
+        }
+
+        // a pool set aside for an outcome nobody holds could never be paid
+        // out, the holders of the other outcomes are refunded instead
+        if event_data
+            .outcomes
+            .iter()
+            .any(|outcome| outcome.payout_pool > 0 && outcome.supply == 0)
+        {
+            Self::refund_pools(event_data)?;
+        }
+        event_data.resolved_at = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Splits the pool between the outcomes still held, each getting back the
+    /// part its holders paid for
+    fn refund_pools(event_data: &mut EventAccount) -> ProgramResult {
+        // market maker trades may have moved the pool away from the total paid,
+        // down to nothing left for the outcomes still held, which then share
+        // it by supply
+        let mut weights = event_data
+            .outcomes
+            .iter()
+            .map(|outcome| if outcome.supply > 0 { outcome.collateral } else { 0 })
+            .collect::<Vec<_>>();
+        if weights.iter().all(|weight| *weight == 0) {
+            weights = event_data.outcomes.iter().map(|outcome| outcome.supply).collect();
+        }
+        let total_weight = weights.iter().map(|weight| *weight as u128).sum::<u128>();
+
+        let volume = event_data.volume;
+        for (outcome, weight) in event_data.outcomes.iter_mut().zip(weights) {
+            outcome.payout_pool = if total_weight > 0 {
+                math::mul_div(volume.into(), weight.into(), total_weight)?
+            } else {
+                0
+            };
+        }
+        Ok(())
+    }
+
+    /// Vote Resolution instruction
+    pub fn vote_resolution(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        resolution: Resolution
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let resolver = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let resolution_account = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id || resolution_account.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !resolver.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if !event_data.has_resolver_set() {
+            return Err(PredictChainError::InvalidResolvers.into());
+        }
+        if *resolution_account.key != event_data.resolution
+            || *resolution_account.key != address::find_resolution_address(program_id, event.key).0
+        {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        if Clock::get()?.unix_timestamp < event_data.close_time {
+            return Err(PredictChainError::TradingNotClosed.into());
+        }
+        Self::check_resolution(&event_data, resolution)?;
+
+        // scalar votes count by the value they settle the event on
+        let resolution = event_data.clamp_resolution(resolution);
+        let mut resolution_data = ResolutionAccount::decode(&resolution_account.data.borrow())?;
+        if resolution_data.event != *event.key {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        let vote = resolution_data
+            .votes
+            .iter_mut()
+            .find(|vote| vote.resolver == *resolver.key)
+            .ok_or(PredictChainError::WrongResolver)?;
+        vote.vote = Some(resolution);
+
+        // conflicting votes are kept but only matching ones count
+        if resolution_data.count_votes(resolution) >= resolution_data.threshold as usize {
+            Self::settle(&mut event_data, resolution)?;
+            event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+        }
+        resolution_data.serialize(&mut &mut resolution_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Redeem Shares instruction
+    pub fn redeem_shares(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        num_tokens: u64
+    ) -> ProgramResult {
+        Self::pay_out_pool(program_id, accounts, num_tokens, EventStatus::Resolved)
+    }
+
+    /// Claim Refund instruction
+    pub fn claim_refund(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        num_tokens: u64
+    ) -> ProgramResult {
+        Self::pay_out_pool(program_id, accounts, num_tokens, EventStatus::Voided)
+    }
+
+    /// Burns shares of an event in the given status for a pro-rata part of
+    /// the payout pool of their outcome
+    fn pay_out_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        num_tokens: u64,
+        status: EventStatus
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let user_signer = next_account_info(account_iter)?;
+        let authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let mint = next_account_info(account_iter)?;
+        let user_token_account = next_account_info(account_iter)?;
+        let token_program_id = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+        let collateral_vault = account_iter.next();
+        let user_collateral_account = account_iter.next();
+
+        Self::unpaused_config(program_id, config)?;
+
+        // num tokens must be > 0
+        if num_tokens == 0 {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !user_signer.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        Self::check_token_program(token_program_id)?;
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if event_data.status != status {
+            return Err(match status {
+                EventStatus::Voided => PredictChainError::EventNotVoided,
+                _ => PredictChainError::NoDecisionMadeYet,
+            }
+            .into());
+        }
+
+        // only tokens of an outcome with a payout can be redeemed
+        let outcome_index = event_data
+            .outcomes
+            .iter()
+            .position(|outcome| outcome.mint_address == *mint.key)
+            .ok_or(PredictChainError::InvalidTokenMint)?;
+        let payout_pool = event_data.outcomes[outcome_index].payout_pool;
+        if payout_pool == 0 {
+            return Err(PredictChainError::NotRedeemable.into());
+        }
+
+        let token_account = Self::unpack_token_account(user_token_account, mint.key)?;
+        if token_account.owner != *user_signer.key {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+
+        let authority_pub_key = Self::authority_id(program_id, event.key, event_data.bump_seed)?;
+        if *authority.key != authority_pub_key {
+            return Err(PredictChainError::InvalidAuthorityAccount.into());
+        }
+
+        // payout is the share of the outcome pool owned by the redeemed tokens
+        let supply = spl_token::state::Mint::unpack(&mint.data.borrow())?.supply;
+        if num_tokens > supply {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
+        let payout = math::mul_div(num_tokens.into(), payout_pool.into(), supply.into())?;
+        let outcome = &mut event_data.outcomes[outcome_index];
+        outcome.payout_pool = math::sub(payout_pool, payout)?;
+        outcome.supply = math::sub(outcome.supply, num_tokens)?;
+
+        // burn redeemed tokens
+        Self::burn(
+            token_program_id.clone(),
+            user_token_account.clone(),
+            mint.clone(),
+            authority.clone(),
+            num_tokens,
+            event.key,
+            event_data.bump_seed,
+        )?;
+
+        Self::pay_out(
+            event,
+            &mut event_data,
+            authority,
+            user_collateral_account.unwrap_or(user_signer),
+            collateral_vault,
+            token_program_id,
+            payout,
+        )?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Sell Shares instruction
+    pub fn sell_shares(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        side_index: u8,
+        num_tokens: u64,
+        min_proceeds: u64
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let user_signer = next_account_info(account_iter)?;
+        let authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let mint = next_account_info(account_iter)?;
+        let user_token_account = next_account_info(account_iter)?;
+        let token_program_id = next_account_info(account_iter)?;
+        let fee_vault = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+        let collateral_vault = account_iter.next();
+        let user_collateral_account = account_iter.next();
+
+        Self::unpaused_config(program_id, config)?;
+
+        // num tokens must be > 0
+        if num_tokens == 0 {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !user_signer.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        Self::check_token_program(token_program_id)?;
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::EventClosed.into());
+        }
+        if !event_data.is_trading_open(Clock::get()?.unix_timestamp) {
+            return Err(PredictChainError::OutsideTradingWindow.into());
+        }
+        if *fee_vault.key != event_data.fee_vault {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+
+        // side index must be a valid outcome
+        let mint_address = event_data
+            .outcomes
+            .get(side_index as usize)
+            .ok_or(PredictChainError::InvalidSide)?
+            .mint_address;
+        if *mint.key != mint_address {
+            return Err(PredictChainError::InvalidTokenMint.into());
+        }
+
+        let token_account = Self::unpack_token_account(user_token_account, &mint_address)?;
+        if token_account.owner != *user_signer.key {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+
+        let authority_pub_key = Self::authority_id(program_id, event.key, event_data.bump_seed)?;
+        if *authority.key != authority_pub_key {
+            return Err(PredictChainError::InvalidAuthorityAccount.into());
+        }
+
+        let proceeds = Self::sale_proceeds(&mut event_data, side_index, num_tokens)?;
+        let (protocol_fee, creator_fee) = event_data.trading_fees(proceeds)?;
+        let fees = math::add(protocol_fee, creator_fee)?;
+        let net_proceeds = math::sub(proceeds, fees)?;
+        if net_proceeds < min_proceeds {
+            msg!("proceeds of {} are below minimum of {}", net_proceeds, min_proceeds);
+            return Err(PredictChainError::SlippageExceeded.into());
+        }
+        // market maker proceeds can exceed what was paid for the outcome
+        let outcome = &mut event_data.outcomes[side_index as usize];
+        outcome.collateral = outcome.collateral.saturating_sub(proceeds);
+        outcome.supply = math::sub(outcome.supply, num_tokens)?;
+
+        // burn sold tokens
+        Self::burn(
+            token_program_id.clone(),
+            user_token_account.clone(),
+            mint.clone(),
+            authority.clone(),
+            num_tokens,
+            event.key,
+            event_data.bump_seed,
+        )?;
+
+        Self::pay_out(
+            event,
+            &mut event_data,
+            authority,
+            user_collateral_account.unwrap_or(user_signer),
+            collateral_vault,
+            token_program_id,
+            net_proceeds,
+        )?;
+        if fees > 0 {
+            Self::pay_out(
+                event,
+                &mut event_data,
+                authority,
+                fee_vault,
+                collateral_vault,
+                token_program_id,
+                fees,
+            )?;
+        }
+        event_data.protocol_fees = math::add(event_data.protocol_fees, protocol_fee)?;
+        event_data.creator_fees = math::add(event_data.creator_fees, creator_fee)?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Mint Complete Set instruction
+    pub fn mint_complete_set(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        num_sets: u64
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let user_signer = next_account_info(account_iter)?;
+        let authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let system_program = next_account_info(account_iter)?;
+        let token_program_id = next_account_info(account_iter)?;
+
+        // num sets must be > 0
+        if num_sets == 0 {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !user_signer.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        Self::check_token_program(token_program_id)?;
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::EventClosed.into());
+        }
+
+        // a set is only worth one share when market maker prices add up to one
+        if event_data.pricing_mode == PricingMode::FixedPrice {
+            return Err(PredictChainError::InvalidPricingMode.into());
+        }
+
+        let (outcome_mints, user_token_accounts) =
+            Self::outcome_accounts(account_iter, &event_data)?;
+        let config = next_account_info(account_iter)?;
+        let collateral_vault = account_iter.next();
+        let user_collateral_account = account_iter.next();
+
+        Self::unpaused_config(program_id, config)?;
+
+        for (user_token_account, outcome) in user_token_accounts.iter().zip(&event_data.outcomes) {
+            Self::unpack_token_account(user_token_account, &outcome.mint_address)?;
+        }
+
+        let authority_pub_key = Self::authority_id(program_id, event.key, event_data.bump_seed)?;
+        if *authority.key != authority_pub_key {
+            return Err(PredictChainError::InvalidAuthorityAccount.into());
+        }
+
+        let cost = math::mul(num_sets, event_data.price_per_share)?;
+        Self::collect_payment(
+            event,
+            &mut event_data,
+            user_signer,
+            system_program,
+            collateral_vault,
+            user_collateral_account,
+            token_program_id,
+            cost,
+        )?;
+        let outcome_collateral = event_data.complete_set_collateral(cost);
+        for (outcome, collateral) in event_data.outcomes.iter_mut().zip(outcome_collateral) {
+            outcome.collateral = math::add(outcome.collateral, collateral)?;
+            outcome.supply = math::add(outcome.supply, num_sets)?;
+        }
+
+        // mint one token of each outcome per set
+        for (mint, user_token_account) in outcome_mints.into_iter().zip(user_token_accounts) {
+            Self::mint(
+                token_program_id.clone(),
+                mint.clone(),
+                user_token_account.clone(),
+                authority.clone(),
+                num_sets,
+                event.key,
+                event_data.bump_seed,
+            )?;
+        }
+
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Merge Complete Set instruction
+    pub fn merge_complete_set(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        num_sets: u64
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let user_signer = next_account_info(account_iter)?;
+        let authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let token_program_id = next_account_info(account_iter)?;
+
+        // num sets must be > 0
+        if num_sets == 0 {
+            return Err(PredictChainError::InvalidAmount.into());
+        }
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !user_signer.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        Self::check_token_program(token_program_id)?;
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::EventClosed.into());
+        }
+
+        // complete sets only exist for market maker events
+        if event_data.pricing_mode == PricingMode::FixedPrice {
+            return Err(PredictChainError::InvalidPricingMode.into());
+        }
+
+        let (outcome_mints, user_token_accounts) =
+            Self::outcome_accounts(account_iter, &event_data)?;
+        let config = next_account_info(account_iter)?;
+        let collateral_vault = account_iter.next();
+        let user_collateral_account = account_iter.next();
+
+        Self::unpaused_config(program_id, config)?;
+
+        for (user_token_account, outcome) in user_token_accounts.iter().zip(&event_data.outcomes) {
+            let token_account =
+                Self::unpack_token_account(user_token_account, &outcome.mint_address)?;
+            if token_account.owner != *user_signer.key {
+                return Err(PredictChainError::WrongOwner.into());
+            }
+        }
+
+        let authority_pub_key = Self::authority_id(program_id, event.key, event_data.bump_seed)?;
+        if *authority.key != authority_pub_key {
+            return Err(PredictChainError::InvalidAuthorityAccount.into());
+        }
+
+        // burn one token of each outcome per set
+        for (mint, user_token_account) in outcome_mints.into_iter().zip(user_token_accounts) {
+            Self::burn(
+                token_program_id.clone(),
+                user_token_account.clone(),
+                mint.clone(),
+                authority.clone(),
+                num_sets,
+                event.key,
+                event_data.bump_seed,
+            )?;
+        }
+
+        let collateral = math::mul(num_sets, event_data.price_per_share)?;
+        let outcome_collateral = event_data.complete_set_collateral(collateral);
+        for (outcome, collateral) in event_data.outcomes.iter_mut().zip(outcome_collateral) {
+            outcome.collateral = outcome.collateral.saturating_sub(collateral);
+            outcome.supply = math::sub(outcome.supply, num_sets)?;
+        }
+        Self::pay_out(
+            event,
+            &mut event_data,
+            authority,
+            user_collateral_account.unwrap_or(user_signer),
+            collateral_vault,
+            token_program_id,
+            collateral,
+        )?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Withdraw Fees instruction
+    pub fn withdraw_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let recipient = next_account_info(account_iter)?;
+        let authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let fee_vault = next_account_info(account_iter)?;
+        let token_program_id = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+        let destination = account_iter.next();
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !recipient.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        Self::check_token_program(token_program_id)?;
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if *fee_vault.key != address::find_fee_vault_address(program_id, event.key).0 {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        let authority_pub_key = Self::authority_id(program_id, event.key, event_data.bump_seed)?;
+        if *authority.key != authority_pub_key {
+            return Err(PredictChainError::InvalidAuthorityAccount.into());
+        }
+
+        if *recipient.key != event_data.creator && *recipient.key != event_data.protocol_fee_recipient {
+            return Err(PredictChainError::WrongFeeRecipient.into());
+        }
+
+        // the creator and the protocol fee recipient may be the same account
+        let mut amount = 0;
+        if *recipient.key == event_data.creator {
+            amount = math::add(amount, event_data.creator_fees)?;
+            event_data.creator_fees = 0;
+        }
+        if *recipient.key == event_data.protocol_fee_recipient {
+            amount = math::add(amount, event_data.protocol_fees)?;
+            event_data.protocol_fees = 0;
+        }
+
+        if event_data.has_token_collateral() {
+            let destination = destination.ok_or(PredictChainError::InvalidCollateralAccount)?;
+            Self::unpack_token_account(destination, &event_data.collateral_mint)
+                .map_err(|_| PredictChainError::InvalidCollateralAccount)?;
+
+            let me_bytes = event.key.to_bytes();
+            let authority_signature_seeds =
+                [AUTHORITY_SEED, &me_bytes[..32], &[event_data.bump_seed]];
+            Self::transfer(
+                token_program_id.clone(),
+                fee_vault.clone(),
+                destination.clone(),
+                authority.clone(),
+                amount,
+                &[&authority_signature_seeds[..]],
+            )?;
+        } else {
+            // the fee vault keeps its rent exemption on top of the fees
+            let fee_vault_lamports = fee_vault
+                .lamports()
+                .checked_sub(amount)
+                .ok_or(PredictChainError::InsufficientFunds)?;
+            let recipient_lamports = math::add(recipient.lamports(), amount)?;
+            **fee_vault.try_borrow_mut_lamports()? = fee_vault_lamports;
+            **recipient.try_borrow_mut_lamports()? = recipient_lamports;
+        }
+
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Propose Resolve Authority instruction
+    pub fn propose_resolve_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_resolve_authority: Pubkey
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let resolve_authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !resolve_authority.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if *resolve_authority.key != event_data.resolve_authority {
+            return Err(PredictChainError::WrongResolveAuthority.into());
+        }
+
+        // a new proposal replaces the pending one
+        event_data.pending_resolve_authority = new_resolve_authority;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Accept Resolve Authority instruction
+    pub fn accept_resolve_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let pending_resolve_authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !pending_resolve_authority.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if event_data.pending_resolve_authority == Pubkey::default() {
+            return Err(PredictChainError::NoPendingResolveAuthority.into());
+        }
+        if *pending_resolve_authority.key != event_data.pending_resolve_authority {
+            return Err(PredictChainError::WrongResolveAuthority.into());
+        }
+
+        event_data.resolve_authority = event_data.pending_resolve_authority;
+        event_data.pending_resolve_authority = Pubkey::default();
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Propose Resolution instruction
+    pub fn propose_resolution(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        resolution: Resolution
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let proposer = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let system_program = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !proposer.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if !event_data.is_optimistic() {
+            return Err(PredictChainError::InvalidProposal.into());
+        }
+        if Clock::get()?.unix_timestamp < event_data.close_time {
+            return Err(PredictChainError::TradingNotClosed.into());
+        }
+        Self::check_resolution(&event_data, resolution)?;
+        if event_data.proposal.status != ProposalStatus::None {
+            return Err(PredictChainError::WrongProposalStatus.into());
+        }
+
+        // proposals settle the event, they can't void it
+        let (winning_side, value) = match resolution {
+            Resolution::Side(winning_side) => (winning_side, 0),
+            Resolution::Value(value) => {
+                (0, value.clamp(event_data.lower_bound, event_data.upper_bound))
+            }
+            Resolution::Void => return Err(PredictChainError::InvalidProposal.into()),
+        };
+
+        Self::post_bond(event, &event_data, proposer, system_program)?;
+        event_data.proposal = Proposal {
+            status: ProposalStatus::Proposed,
+            proposer: *proposer.key,
+            disputer: Pubkey::default(),
+            winning_side,
+            value,
+            proposed_at: Clock::get()?.unix_timestamp,
+        };
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Dispute Resolution instruction
+    pub fn dispute_resolution(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let disputer = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let system_program = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        // the dispute window keeps running while the program is paused, so
+        // proposals stay disputable
+        Self::load_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !disputer.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::DecisionAlreadyMade.into());
+        }
+        if event_data.proposal.status != ProposalStatus::Proposed {
+            return Err(PredictChainError::WrongProposalStatus.into());
+        }
+        if Clock::get()?.unix_timestamp >= event_data.dispute_deadline() {
+            return Err(PredictChainError::DisputeWindowClosed.into());
+        }
+
+        Self::post_bond(event, &event_data, disputer, system_program)?;
+        event_data.proposal.status = ProposalStatus::Disputed;
+        event_data.proposal.disputer = *disputer.key;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Finalize Resolution instruction
+    pub fn finalize_resolution(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let proposer = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::DecisionAlreadyMade.into());
+        }
+        if event_data.proposal.status != ProposalStatus::Proposed {
+            return Err(PredictChainError::WrongProposalStatus.into());
+        }
+        if *proposer.key != event_data.proposal.proposer {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        if Clock::get()?.unix_timestamp < event_data.dispute_deadline() {
+            return Err(PredictChainError::DisputeWindowOpen.into());
+        }
+
+        let resolution = event_data.proposal.resolution(event_data.event_type);
+        Self::settle(&mut event_data, resolution)?;
+        Self::return_bonds(event, proposer, event_data.proposal_bond)?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Settle Dispute instruction
+    pub fn settle_dispute(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        resolution: Resolution
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let resolve_authority = next_account_info(account_iter)?;
+        let event = next_account_info(account_iter)?;
+        let proposer = next_account_info(account_iter)?;
+        let disputer = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        if !resolve_authority.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if *resolve_authority.key != event_data.resolve_authority {
+            return Err(PredictChainError::WrongResolveAuthority.into());
+        }
+        if event_data.status != EventStatus::Open {
+            return Err(PredictChainError::DecisionAlreadyMade.into());
+        }
+        if event_data.proposal.status != ProposalStatus::Disputed {
+            return Err(PredictChainError::WrongProposalStatus.into());
+        }
+        if *proposer.key != event_data.proposal.proposer
+            || *disputer.key != event_data.proposal.disputer
+        {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+
+        Self::settle(&mut event_data, resolution)?;
+
+        // the right party takes both bonds, the proposal being right if the
+        // event settles as proposed
+        let proposal_confirmed = event_data.status == EventStatus::Resolved
+            && match event_data.event_type {
+                EventType::Categorical => event_data.winning_side == event_data.proposal.winning_side,
+                EventType::Scalar => event_data.resolved_value == event_data.proposal.value,
+            };
+        let winner = if proposal_confirmed { proposer } else { disputer };
+        Self::return_bonds(event, winner, math::add(event_data.proposal_bond, event_data.proposal_bond)?)?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Resolve From Oracle instruction
+    pub fn resolve_from_oracle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let event = next_account_info(account_iter)?;
+        let oracle = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        Self::unpaused_config(program_id, config)?;
+
+        if event.owner != program_id {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+
+        let mut event_data = Self::load_event(program_id, event)?;
+        if !event_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if !event_data.has_oracle() {
+            return Err(PredictChainError::InvalidOracle.into());
+        }
+        if *oracle.key != event_data.oracle {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        // anyone can write a price account layout into an account they own
+        if *oracle.owner != event_data.oracle_program {
+            return Err(PredictChainError::InvalidOracle.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if now < event_data.close_time {
+            return Err(PredictChainError::TradingNotClosed.into());
+        }
+
+        let feed = PriceFeed::load(&oracle.data.borrow())?;
+        if feed.expo != event_data.price_expo {
+            return Err(PredictChainError::InvalidOracle.into());
+        }
+        // the price must have been published after trading closed, and recently
+        if feed.timestamp < event_data.close_time
+            || now.saturating_sub(feed.timestamp) > event_data.max_staleness
+        {
+            return Err(PredictChainError::StalePrice.into());
+        }
+        if !feed.is_reliable(event_data.max_confidence_bps) {
+            return Err(PredictChainError::UncertainPrice.into());
+        }
+
+        let resolution = match event_data.event_type {
+            EventType::Categorical if feed.price >= event_data.strike => Resolution::Side(0),
+            EventType::Categorical => Resolution::Side(1),
+            EventType::Scalar => Resolution::Value(feed.price),
+        };
+        Self::settle(&mut event_data, resolution)?;
+        event_data.serialize(&mut &mut event.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Moves the proposal bond of an event from `poster` into the event account
+    fn post_bond<'a>(
+        event: &AccountInfo<'a>,
+        event_data: &EventAccount,
+        poster: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        if *system_program.key != system_program::id() {
+            return Err(PredictChainError::InvalidSystemProgram.into());
+        }
+
+        invoke(
+            &system_instruction::transfer(poster.key, event.key, event_data.proposal_bond),
+            &[poster.clone(), event.clone(), system_program.clone()],
+        )
+    }
+
+    /// Pays `amount` of the bonds held by the event account to `recipient`
+    fn return_bonds(
+        event: &AccountInfo,
+        recipient: &AccountInfo,
+        amount: u64,
+    ) -> ProgramResult {
+        let event_lamports = math::sub(event.lamports(), amount)?;
+        let recipient_lamports = math::add(recipient.lamports(), amount)?;
+        **event.try_borrow_mut_lamports()? = event_lamports;
+        **recipient.try_borrow_mut_lamports()? = recipient_lamports;
+
+        Ok(())
+    }
+
+    /// Initialize Config instruction
+    pub fn initialize_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        args: ConfigArgs
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let upgrade_authority = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+        let system_program = next_account_info(account_iter)?;
+        let program_data = next_account_info(account_iter)?;
+
+        if !upgrade_authority.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        if *system_program.key != system_program::id() {
+            return Err(PredictChainError::InvalidSystemProgram.into());
+        }
+        // the first admin is set by whoever can upgrade the program anyway
+        Self::check_upgrade_authority(program_id, program_data, upgrade_authority)?;
+        let (config_address, bump_seed) = address::find_config_address(program_id);
+        if *config.key != config_address {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+
+        Self::create_derived_account(
+            upgrade_authority,
+            config,
+            system_program,
+            &Rent::get()?,
+            &[CONFIG_SEED, &[bump_seed]],
+            Config::LEN,
+            program_id,
+        )?;
+        if Config::decode(&config.data.borrow())?.is_initialized() {
+            return Err(PredictChainError::AlreadyInUse.into());
+        }
+
+        let config_data = Config {
+            bump_seed,
+            ..Config::default()
+        };
+        Self::write_config(config, config_data, args)
+    }
+
+    /// Update Config instruction
+    pub fn update_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        args: ConfigArgs
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let admin = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        let config_data = Self::admin_config(program_id, admin, config)?;
+        Self::write_config(config, config_data, args)
+    }
+
+    /// Set Paused instruction
+    pub fn set_paused(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool
+    ) -> ProgramResult {
+        let account_iter = &mut accounts.iter();
+        let admin = next_account_info(account_iter)?;
+        let config = next_account_info(account_iter)?;
+
+        let mut config_data = Self::admin_config(program_id, admin, config)?;
+        config_data.paused = paused;
+        config_data.serialize(&mut &mut config.data.borrow_mut()[..])?;
 
         Ok(())
     }
 
+    /// Checks that `upgrade_authority` is the upgrade authority stored in the
+    /// program data account of the program
+    fn check_upgrade_authority(
+        program_id: &Pubkey,
+        program_data: &AccountInfo,
+        upgrade_authority: &AccountInfo,
+    ) -> ProgramResult {
+        if *program_data.key != address::find_program_data_address(program_id).0 {
+            return Err(PredictChainError::WrongAccountAddress.into());
+        }
+        if *program_data.owner != bpf_loader_upgradeable::id() {
+            return Err(PredictChainError::WrongOwner.into());
+        }
+        match bincode::deserialize(&program_data.data.borrow()) {
+            Ok(UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address: Some(authority),
+                ..
+            }) if authority == *upgrade_authority.key => Ok(()),
+            _ => Err(PredictChainError::WrongUpgradeAuthority.into()),
+        }
+    }
+
+    /// Loads an initialized program config, checking that its admin signed
+    fn admin_config(
+        program_id: &Pubkey,
+        admin: &AccountInfo,
+        config: &AccountInfo,
+    ) -> Result<Config, ProgramError> {
+        if !admin.is_signer {
+            return Err(PredictChainError::SignatureMissing.into());
+        }
+        let config_data = Self::load_config(program_id, config)?;
+        if !config_data.is_initialized() {
+            return Err(PredictChainError::UninitializedAccount.into());
+        }
+        if *admin.key != config_data.admin {
+            return Err(PredictChainError::WrongAdmin.into());
+        }
+        Ok(config_data)
+    }
+
+    /// Writes the admin and fee settings of the program config
+    fn write_config(
+        config: &AccountInfo,
+        config_data: Config,
+        args: ConfigArgs,
+    ) -> ProgramResult {
+        // an admin must remain to manage the program
+        if args.admin == Pubkey::default() {
+            return Err(PredictChainError::WrongAdmin.into());
+        }
+        if args.protocol_fee_bps > EventAccount::MAX_FEE_BPS {
+            return Err(PredictChainError::InvalidFee.into());
+        }
+
+        let config_data = Config {
+            admin: args.admin,
+            protocol_fee_bps: args.protocol_fee_bps,
+            protocol_fee_recipient: args.protocol_fee_recipient,
+            oracle_program: args.oracle_program,
+            ..config_data
+        };
+        config_data.serialize(&mut &mut config.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
 }
@@ -1,24 +1,484 @@
 //! State transition types
 
+use crate::{error::PredictChainError, math};
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use solana_program::{borsh::try_from_slice_unchecked, program_error::ProgramError, pubkey::Pubkey};
+
+/// Lifecycle status of an event
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EventStatus {
+    /// Shares can be purchased
+    Open,
+    /// Outcome has been decided by the resolve authority or the resolver set
+    Resolved,
+    /// Event was cancelled by the resolve authority or the resolver set,
+    /// holders are refunded
+    Voided,
+}
+
+/// How the cost of purchased shares is computed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PricingMode {
+    /// Every share costs `price_per_share`
+    FixedPrice,
+    /// Shares are priced from virtual YES/NO reserves by a constant-product
+    /// market maker, `price_per_share` being the value of a full share
+    ConstantProduct,
+    /// Shares are priced by a logarithmic market scoring rule market maker,
+    /// `price_per_share` being the value of a full share, for events of at
+    /// most `EventAccount::MAX_LMSR_OUTCOMES` outcomes
+    Lmsr,
+}
+
+/// What an event settles on
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EventType {
+    /// A single winning outcome takes the whole pool
+    Categorical,
+    /// A numeric value splits the pool between a LONG and a SHORT outcome
+    /// proportionally to where it falls between the bounds
+    Scalar,
+}
+
+/// Outcome struct, one of the mutually exclusive results of an event.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Outcome {
+    /// mint_address, token minted for shares of the outcome
+    pub mint_address: Pubkey,
+    /// label, human readable name of the outcome
+    pub label: String,
+    /// reserve, virtual reserve of the constant-product market maker in lamports
+    pub reserve: u64,
+    /// quantity, shares sold by the LMSR market maker
+    pub quantity: u64,
+    /// payout_pool, part of the pool left to the outcome holders once resolved
+    /// or voided
+    pub payout_pool: u64,
+    /// collateral, paid into the pool for shares of the outcome net of sales
+    pub collateral: u64,
+    /// supply, tokens of the outcome minted and not yet burned by the program
+    pub supply: u64,
+}
+
+impl Outcome {
+    /// Length of serialized data, without the label bytes
+    pub const BASE_LEN: usize = 76;
+}
 
 /// EventAccount struct.
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct EventAccount {
     /// bump_seed
     pub bump_seed: u8,
+    /// event_bump_seed, bump seed of the event address
+    pub event_bump_seed: u8,
     /// resolve_authority
     pub resolve_authority: Pubkey,
-    /// yes_mint_address
-    pub yes_mint_address: Pubkey,
-    /// no_mint_address:
-    pub no_mint_address: Pubkey,
+    /// pending_resolve_authority, proposed by the resolve authority and
+    /// becoming it once it accepts, default if none
+    pub pending_resolve_authority: Pubkey,
+    /// resolution, account recording the votes of the resolver set deciding
+    /// the event, default if the resolve authority decides alone
+    pub resolution: Pubkey,
+    /// dispute_window, seconds during which a proposed resolution can be
+    /// disputed, zero if the resolve authority decides alone
+    pub dispute_window: i64,
+    /// proposal_bond, lamports posted to propose or to dispute a resolution
+    pub proposal_bond: u64,
+    /// proposal, optimistic resolution of the event
+    pub proposal: Proposal,
+    /// oracle, price account resolving the event, default if none
+    pub oracle: Pubkey,
+    /// oracle_program, owner the price account must have, taken from the
+    /// config when the event was created
+    pub oracle_program: Pubkey,
+    /// strike, oracle price from which a binary event resolves to its first
+    /// outcome
+    pub strike: i64,
+    /// price_expo, exponent of the oracle price, the strike and the scalar
+    /// bounds being expressed with it
+    pub price_expo: i32,
+    /// max_staleness, seconds between the publication of the oracle price and
+    /// the resolution
+    pub max_staleness: i64,
+    /// max_confidence_bps, widest confidence interval of the oracle price
+    /// accepted, in basis points of the price
+    pub max_confidence_bps: u16,
+    /// creator, allowed to edit the metadata before trading opens
+    pub creator: Pubkey,
+    /// event_id, chosen by the creator to derive the event addresses
+    pub event_id: u64,
+    /// metadata, account holding the event metadata
+    pub metadata: Pubkey,
     /// volume
-    pub volume: u64
+    pub volume: u64,
+    /// protocol_fees, collected for the protocol fee recipient and not yet withdrawn
+    pub protocol_fees: u64,
+    /// creator_fees, collected for the creator and not yet withdrawn
+    pub creator_fees: u64,
+    /// protocol_fee_bps, protocol fee rate on trades in basis points
+    pub protocol_fee_bps: u16,
+    /// creator_fee_bps, creator fee rate on trades in basis points
+    pub creator_fee_bps: u16,
+    /// protocol_fee_recipient, allowed to withdraw the protocol fees
+    pub protocol_fee_recipient: Pubkey,
+    /// fee_vault, account holding the fees, a token account owned by the
+    /// authority for SPL collateral events
+    pub fee_vault: Pubkey,
+    /// price_per_share, in lamports or collateral token units
+    pub price_per_share: u64,
+    /// pricing_mode
+    pub pricing_mode: PricingMode,
+    /// liquidity, LMSR liquidity parameter b in shares
+    pub liquidity: u64,
+    /// event_type
+    pub event_type: EventType,
+    /// lower_bound, scalar value paying everything to SHORT, scalar events only
+    pub lower_bound: i64,
+    /// upper_bound, scalar value paying everything to LONG, scalar events only
+    pub upper_bound: i64,
+    /// open_time, unix timestamp from which shares can be traded
+    pub open_time: i64,
+    /// close_time, unix timestamp from which shares can no longer be traded
+    pub close_time: i64,
+    /// status
+    pub status: EventStatus,
+    /// winning_side, index of the winning outcome, only meaningful once a
+    /// categorical event is resolved
+    pub winning_side: u8,
+    /// resolved_value, only meaningful once a scalar event is resolved
+    pub resolved_value: i64,
+    /// resolved_at, unix timestamp of the resolution
+    pub resolved_at: i64,
+    /// collateral_mint, SPL token the event is priced in, default for lamports
+    pub collateral_mint: Pubkey,
+    /// collateral_vault, token account holding the collateral, owned by the authority
+    pub collateral_vault: Pubkey,
+    /// outcomes
+    pub outcomes: Vec<Outcome>,
 }
 
 impl EventAccount {
-    /// Length serialized data
-    pub const LEN: usize = 105;
+    /// Length of serialized data, without the outcomes
+    pub const BASE_LEN: usize = 582;
+
+    /// Maximum number of outcomes of an event
+    pub const MAX_OUTCOMES: usize = 16;
+
+    /// Maximum number of outcomes of an LMSR event, the cost function
+    /// evaluates a series per outcome twice on every trade and has to fit in
+    /// the compute budget of a transaction
+    pub const MAX_LMSR_OUTCOMES: usize = 4;
+
+    /// Maximum length of an outcome label, in bytes
+    pub const MAX_LABEL_LEN: usize = 32;
+
+    /// Maximum of the protocol and creator fee rates combined, in basis points
+    pub const MAX_FEE_BPS: u16 = 1_000;
+
+    /// Basis points in a whole
+    pub const BPS_DENOMINATOR: u128 = 10_000;
+
+    /// Length of serialized data of an event with the given outcome labels
+    pub fn packed_len(outcome_labels: &[String]) -> usize {
+        outcome_labels
+            .iter()
+            .map(|label| Outcome::BASE_LEN + label.len())
+            .sum::<usize>()
+            + Self::BASE_LEN
+    }
+
+    /// Check if the event has been initialized by `CreateEvent`
+    pub fn is_initialized(&self) -> bool {
+        !self.outcomes.is_empty()
+    }
+
+    /// Check if shares can be traded at the given unix timestamp
+    pub fn is_trading_open(&self, unix_timestamp: i64) -> bool {
+        (self.open_time..self.close_time).contains(&unix_timestamp)
+    }
+
+    /// Shares sold by the LMSR market maker, per outcome
+    pub fn quantities(&self) -> Vec<u64> {
+        self.outcomes.iter().map(|outcome| outcome.quantity).collect()
+    }
+
+    /// Splits the collateral deposited for complete sets evenly between the
+    /// outcomes, the first outcome taking the remainder
+    pub fn complete_set_collateral(&self, amount: u64) -> Vec<u64> {
+        let num_outcomes = self.outcomes.len() as u64;
+        let share = amount / num_outcomes;
+        let mut shares = vec![share; self.outcomes.len()];
+        shares[0] += amount % num_outcomes;
+        shares
+    }
+
+    /// Protocol and creator fees on a trade of `amount`, each rounded down
+    pub fn trading_fees(&self, amount: u64) -> Result<(u64, u64), PredictChainError> {
+        let protocol_fee =
+            math::mul_div(amount.into(), self.protocol_fee_bps.into(), Self::BPS_DENOMINATOR)?;
+        let creator_fee =
+            math::mul_div(amount.into(), self.creator_fee_bps.into(), Self::BPS_DENOMINATOR)?;
+        Ok((protocol_fee, creator_fee))
+    }
+
+    /// Resolution as it settles the event, a scalar value being clamped to
+    /// the event bounds
+    pub fn clamp_resolution(&self, resolution: Resolution) -> Resolution {
+        match resolution {
+            Resolution::Value(value) => {
+                Resolution::Value(value.clamp(self.lower_bound, self.upper_bound))
+            }
+            resolution => resolution,
+        }
+    }
+
+    /// Check if the event is priced in an SPL token rather than lamports
+    pub fn has_token_collateral(&self) -> bool {
+        self.collateral_mint != Pubkey::default()
+    }
+
+    /// Check if the event is decided by votes of a resolver set rather than by
+    /// its resolve authority
+    pub fn has_resolver_set(&self) -> bool {
+        self.resolution != Pubkey::default()
+    }
+
+    /// Check if the event is decided by bonded proposals, the resolve
+    /// authority only deciding disputed ones
+    pub fn is_optimistic(&self) -> bool {
+        self.dispute_window > 0
+    }
+
+    /// Check if the event resolves itself from an oracle price account
+    pub fn has_oracle(&self) -> bool {
+        self.oracle != Pubkey::default()
+    }
+
+    /// Unix timestamp from which the proposed resolution can no longer be
+    /// disputed
+    pub fn dispute_deadline(&self) -> i64 {
+        self.proposal.proposed_at.saturating_add(self.dispute_window)
+    }
+}
+
+/// Decision settling an event
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+    /// Index of the winning outcome of a categorical event
+    Side(u8),
+    /// Observed value of a scalar event, clamped to the event bounds
+    Value(i64),
+    /// Event is cancelled, holders are refunded
+    Void,
+}
+
+/// Stage of the optimistic resolution of an event
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ProposalStatus {
+    /// No resolution is proposed
+    None,
+    /// Resolution is proposed and settles the event once the dispute window
+    /// ends without a dispute
+    Proposed,
+    /// Proposed resolution is disputed, the resolve authority decides
+    Disputed,
+}
+
+/// Proposal struct, resolution proposed with a bond, of fixed length.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Proposal {
+    /// status
+    pub status: ProposalStatus,
+    /// proposer, posted the proposal bond
+    pub proposer: Pubkey,
+    /// disputer, posted the dispute bond, default until disputed
+    pub disputer: Pubkey,
+    /// winning_side, proposed winning outcome of a categorical event
+    pub winning_side: u8,
+    /// value, proposed value of a scalar event
+    pub value: i64,
+    /// proposed_at, unix timestamp from which the dispute window runs
+    pub proposed_at: i64,
+}
+
+impl Proposal {
+    /// Proposal of an event that has none
+    pub fn none() -> Self {
+        Self {
+            status: ProposalStatus::None,
+            proposer: Pubkey::default(),
+            disputer: Pubkey::default(),
+            winning_side: 0,
+            value: 0,
+            proposed_at: 0,
+        }
+    }
+
+    /// Proposed resolution of an event of type `event_type`
+    pub fn resolution(&self, event_type: EventType) -> Resolution {
+        match event_type {
+            EventType::Categorical => Resolution::Side(self.winning_side),
+            EventType::Scalar => Resolution::Value(self.value),
+        }
+    }
+}
+
+/// Vote of a resolver, one per member of the resolver set
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ResolverVote {
+    /// resolver, member of the resolver set
+    pub resolver: Pubkey,
+    /// vote, latest resolution voted by the resolver, none until it votes
+    pub vote: Option<Resolution>,
+}
+
+impl ResolverVote {
+    /// Length of serialized data, fitting the largest vote
+    pub const LEN: usize = 32 + 1 + 9;
+}
+
+/// ResolutionAccount struct, votes of the resolver set of an event kept in
+/// their own account. Votes stay recorded once the event is settled.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct ResolutionAccount {
+    /// event, account the resolver set decides
+    pub event: Pubkey,
+    /// threshold, number of matching votes settling the event
+    pub threshold: u8,
+    /// votes, of every resolver in order
+    pub votes: Vec<ResolverVote>,
+}
+
+impl ResolutionAccount {
+    /// Length of serialized data, without the votes
+    pub const BASE_LEN: usize = 32 + 1 + 4;
+
+    /// Maximum number of resolvers of an event
+    pub const MAX_RESOLVERS: usize = 16;
+
+    /// Length of the resolution account of `num_resolvers` resolvers, fitting
+    /// the largest votes
+    pub fn packed_len(num_resolvers: usize) -> usize {
+        Self::BASE_LEN + num_resolvers * ResolverVote::LEN
+    }
+
+    /// Decodes the votes from the data of a resolution account, which is
+    /// padded up to `packed_len` bytes
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Ok(try_from_slice_unchecked::<Self>(data)?)
+    }
+
+    /// Check if the resolution account has been initialized by `CreateEvent`
+    pub fn is_initialized(&self) -> bool {
+        self.event != Pubkey::default()
+    }
+
+    /// Number of resolvers whose latest vote is `resolution`
+    pub fn count_votes(&self, resolution: Resolution) -> usize {
+        self.votes
+            .iter()
+            .filter(|vote| vote.vote == Some(resolution))
+            .count()
+    }
+}
+
+/// EventMetadata struct, descriptive data of an event kept in its own account.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct EventMetadata {
+    /// event, account the metadata describes
+    pub event: Pubkey,
+    /// title
+    pub title: String,
+    /// description
+    pub description: String,
+    /// league, e.g. the sports league or election the event belongs to
+    pub league: String,
+    /// category
+    pub category: String,
+    /// image0, URI of the image of the first outcome
+    pub image0: String,
+    /// image1, URI of the image of the second outcome
+    pub image1: String,
+}
+
+impl EventMetadata {
+    /// Maximum length of the title, in bytes
+    pub const MAX_TITLE_LEN: usize = 64;
+
+    /// Maximum length of the description, in bytes
+    pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+    /// Maximum length of the league and the category, in bytes
+    pub const MAX_TAG_LEN: usize = 32;
+
+    /// Maximum length of an image URI, in bytes
+    pub const MAX_URI_LEN: usize = 128;
+
+    /// Length of the metadata account, fitting strings of maximum length
+    pub const LEN: usize = 32
+        + 6 * 4
+        + Self::MAX_TITLE_LEN
+        + Self::MAX_DESCRIPTION_LEN
+        + 2 * Self::MAX_TAG_LEN
+        + 2 * Self::MAX_URI_LEN;
+
+    /// Decodes the metadata from the data of a metadata account, which is
+    /// padded up to `LEN` bytes
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Ok(try_from_slice_unchecked::<Self>(data)?)
+    }
+
+    /// Check if the metadata has been initialized by `CreateEvent`
+    pub fn is_initialized(&self) -> bool {
+        self.event != Pubkey::default()
+    }
+
+    /// Check if every string fits its maximum length
+    pub fn is_valid(&self) -> bool {
+        self.title.len() <= Self::MAX_TITLE_LEN
+            && self.description.len() <= Self::MAX_DESCRIPTION_LEN
+            && self.league.len() <= Self::MAX_TAG_LEN
+            && self.category.len() <= Self::MAX_TAG_LEN
+            && self.image0.len() <= Self::MAX_URI_LEN
+            && self.image1.len() <= Self::MAX_URI_LEN
+    }
+}
+
+/// Config struct, program-wide settings kept in a single account at the
+/// address given by `address::find_config_address`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    /// bump_seed
+    pub bump_seed: u8,
+    /// admin, allowed to update the config and to pause the program
+    pub admin: Pubkey,
+    /// protocol_fee_bps, protocol fee rate of the events created from now on
+    pub protocol_fee_bps: u16,
+    /// protocol_fee_recipient, of the events created from now on
+    pub protocol_fee_recipient: Pubkey,
+    /// paused, every state-changing instruction fails while set
+    pub paused: bool,
+    /// oracle_program, owner of the price accounts of the oracle events
+    /// created from now on, default if oracle events are disabled
+    pub oracle_program: Pubkey,
+}
+
+impl Config {
+    /// Length of the config account
+    pub const LEN: usize = 100;
+
+    /// Decodes the config from the data of the config account, an account
+    /// that was never allocated holding the default config
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(Self::try_from_slice(data)?)
+    }
+
+    /// Check if the config has been initialized by `InitializeConfig`
+    pub fn is_initialized(&self) -> bool {
+        self.admin != Pubkey::default()
+    }
 }
@@ -1,307 +1,5040 @@
 // #![cfg(feature = "test-bpf")]
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::{hash::Hash, program_pack::Pack, pubkey::Pubkey, system_instruction};
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock, hash::Hash, instruction::Instruction, program_option::COption,
+    program_pack::Pack, pubkey::Pubkey, rent::Rent, system_instruction,
+    system_instruction::SystemError, system_program, sysvar,
+};
 use solana_program_test::*;
 use solana_sdk::{
-    account::Account,
+    account::{self as sdk_account, Account},
+    instruction::InstructionError,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    signer::keypair::keypair_from_seed,
+    transaction::{Transaction, TransactionError},
     transport::TransportError,
 };
 use predictchain::*;
+use proptest::prelude::*;
 
 
 pub fn program_test() -> ProgramTest {
-    ProgramTest::new(
+    let mut program_test = ProgramTest::new(
         "predictchain",
         id(),
         processor!(processor::Processor::process_instruction),
-    )
+    );
+
+    // native programs can't allocate accounts through a CPI, so the config
+    // account InitializeConfig allocates on chain is allocated at genesis
+    let (config, _) = address::find_config_address(&id());
+    program_test.add_account(config, Account {
+        lamports: Rent::default().minimum_balance(state::Config::LEN),
+        data: vec![0; state::Config::LEN],
+        owner: id(),
+        ..Account::default()
+    });
+
+    // the program isn't deployed by the upgradeable loader either, so its
+    // program data account only holds the upgrade authority
+    let (program_data, _) = address::find_program_data_address(&id());
+    let program_data_state = UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(upgrade_authority().pubkey()),
+    };
+    let data = bincode::serialize(&program_data_state).unwrap();
+    program_test.add_account(program_data, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        ..Account::default()
+    });
+    program_test
+}
+
+/// Starts the program test, with clones of the banks client and payer of the
+/// context to use alongside it
+pub async fn start() -> (ProgramTestContext, BanksClient, Keypair, Hash) {
+    let context = program_test().start_with_context().await;
+    let banks_client = context.banks_client.clone();
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let recent_blockhash = context.last_blockhash;
+    (context, banks_client, payer, recent_blockhash)
+}
+
+/// Upgrade authority of the program, allowed to initialize its config
+pub fn upgrade_authority() -> Keypair {
+    keypair_from_seed(&[7; 32]).unwrap()
+}
+
+/// Oracle program owning the price accounts, set by `initialize_config`
+pub fn oracle_program() -> Pubkey {
+    Pubkey::new_from_array([3; 32])
 }
 
 pub struct TestEvent {
-    pub event_account: Keypair,
+    pub creator: Keypair,
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub metadata_account: Pubkey,
     pub authority: Pubkey,
     pub bump_seed: u8,
-    pub yes_mint: Keypair,
-    pub no_mint: Keypair
+    pub outcome_labels: Vec<String>,
+    pub outcome_mints: Vec<Pubkey>,
+    pub price_per_share: u64,
+    pub pricing_mode: state::PricingMode,
+    pub liquidity: u64,
+    pub event_type: state::EventType,
+    pub lower_bound: i64,
+    pub upper_bound: i64,
+    pub open_time: i64,
+    pub close_time: i64,
+    pub metadata: instruction::EventMetadataArgs,
+    pub collateral_mint: Option<Pubkey>,
+    pub collateral_vault: Pubkey,
+    pub creator_fee_bps: u16,
+    pub resolvers: Vec<Pubkey>,
+    pub resolution_threshold: u8,
+    pub dispute_window: i64,
+    pub proposal_bond: u64,
+    pub oracle: Pubkey,
+    pub strike: i64,
+    pub price_expo: i32,
+    pub max_staleness: i64,
+    pub max_confidence_bps: u16
+}
+
+impl Default for TestEvent {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TestEvent {
     pub fn new() -> Self {
-        let event_account = Keypair::new();
-        let (authority, bump_seed) =
-            Pubkey::find_program_address(&[&event_account.pubkey().to_bytes()[..32]], &id());
+        Self::with_outcomes(&["YES", "NO"])
+    }
+
+    pub fn with_outcomes(labels: &[&str]) -> Self {
+        let creator = Keypair::new();
+        let event_id = 1;
+        let (event_account, _) = address::find_event_address(&id(), &creator.pubkey(), event_id);
+        let (authority, bump_seed) = address::find_authority_address(&id(), &event_account);
         Self {
             event_account,
+            metadata_account: address::find_metadata_address(&id(), &event_account).0,
             authority,
             bump_seed,
-            yes_mint: Keypair::new(),
-            no_mint: Keypair::new()
+            outcome_labels: labels.iter().map(|label| label.to_string()).collect(),
+            outcome_mints: (0..labels.len() as u8)
+                .map(|index| address::find_mint_address(&id(), &event_account, index).0)
+                .collect(),
+            price_per_share: 1_000_000,
+            pricing_mode: state::PricingMode::FixedPrice,
+            liquidity: 0,
+            event_type: state::EventType::Categorical,
+            lower_bound: 0,
+            upper_bound: 0,
+            open_time: 0,
+            close_time: i64::MAX,
+            metadata: instruction::EventMetadataArgs::default(),
+            collateral_mint: None,
+            collateral_vault: address::find_vault_address(&id(), &event_account).0,
+            creator_fee_bps: 0,
+            resolvers: vec![],
+            resolution_threshold: 0,
+            dispute_window: 0,
+            proposal_bond: 0,
+            oracle: Pubkey::default(),
+            strike: 0,
+            price_expo: 0,
+            max_staleness: 0,
+            max_confidence_bps: 0,
+            creator,
+            event_id
+        }
+    }
+
+    pub fn resolution(&self) -> Pubkey {
+        address::find_resolution_address(&id(), &self.event_account).0
+    }
+
+    /// Fee vault, a token account for SPL collateral events
+    pub fn fee_vault(&self) -> Pubkey {
+        address::find_fee_vault_address(&id(), &self.event_account).0
+    }
+
+    /// Allocates and assigns the accounts of the event as `CreateEvent` does
+    /// through the system program, whose changes to the size and owner of an
+    /// account native programs can't pick up. Lamports already sent to an
+    /// address are kept for the program to top up.
+    pub async fn preload_accounts(&self, context: &mut ProgramTestContext) {
+        let fee_vault_len = match self.collateral_mint {
+            Some(_) => spl_token::state::Account::LEN,
+            None => 0,
+        };
+        let fee_vault_owner = match self.collateral_mint {
+            Some(_) => spl_token::id(),
+            None => id(),
+        };
+        let mut accounts = vec![
+            (self.event_account, state::EventAccount::packed_len(&self.outcome_labels), id()),
+            (self.metadata_account, state::EventMetadata::LEN, id()),
+            (self.fee_vault(), fee_vault_len, fee_vault_owner),
+        ];
+        for mint in &self.outcome_mints {
+            accounts.push((*mint, spl_token::state::Mint::LEN, spl_token::id()));
+        }
+        if !self.resolvers.is_empty() {
+            let resolution_len = state::ResolutionAccount::packed_len(self.resolvers.len());
+            accounts.push((self.resolution(), resolution_len, id()));
+        }
+        if self.collateral_mint.is_some() {
+            accounts.push((self.collateral_vault, spl_token::state::Account::LEN, spl_token::id()));
+        }
+
+        for (address, len, owner) in accounts {
+            let lamports = match context.banks_client.get_account(address).await.unwrap() {
+                Some(account) if account.owner == system_program::id() => account.lamports,
+                Some(_) => continue,
+                None => Rent::default().minimum_balance(len),
+            };
+            let account = Account {
+                lamports,
+                data: vec![0; len],
+                owner,
+                ..Account::default()
+            };
+            context.set_account(&address, &account.into());
         }
     }
 
     pub async fn init_event(
         &self,
-        banks_client: &mut BanksClient,
+        context: &mut ProgramTestContext,
         payer: &Keypair,
         recent_blockhash: &Hash,
     ) {
-        let rent = banks_client.get_rent().await.unwrap();
-        let event_rent = rent.minimum_balance(state::EventAccount::LEN);
-        let mint_rent = rent.minimum_balance(spl_token::state::Mint::LEN);
-
-        // create event account
-        create_account(
-            banks_client,
-            payer,
-            recent_blockhash,
-            &self.event_account,
-            event_rent,
-            state::EventAccount::LEN as u64,
-            &id(),
-        ).await.unwrap();
-
-        println!("REAL bumpseed: {}", self.bump_seed);
-        let mut asdf = banks_client.get_account(self.event_account.pubkey()).await.expect("account not found").expect("account empty");
-        let mut event_data = state::EventAccount::try_from_slice(&asdf.data).unwrap();
-        event_data.bump_seed = self.bump_seed;
-        event_data.serialize(&mut &mut asdf.data[..]).unwrap();
-
-        let asdf1 = banks_client.get_account(self.event_account.pubkey()).await.expect("account not found").expect("account empty");
-        let event_data1 = state::EventAccount::try_from_slice(&asdf1.data).unwrap();
-        println!("bumpseed: {}", event_data1.bump_seed);
-
-        println!("event key: {}", self.event_account.pubkey());
-
-        println!("authority: {}", self.authority);
-
-
-        // println!("LEN: {}", state::EventAccount::LEN);
-
-        // let asdf = banks_client.get_account(self.event_account.pubkey()).await.expect("account not found").expect("account empty");
-        // println!("actual LEN: {}", asdf.data.len());
-        // let event_data = state::EventAccount::try_from_slice(&asdf.data).unwrap();
-        // println!("volume: {}", event_data.volume);
+        self.try_init_event(context, payer, recent_blockhash).await.unwrap();
+    }
 
-        // println!("ASDLKSJDF LKSJD F");
-        // println!("{}", self.event_account.pubkey());
+    pub async fn try_init_event(
+        &self,
+        context: &mut ProgramTestContext,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+    ) -> Result<(), TransportError> {
+        self.preload_accounts(context).await;
+        let create_event_args = instruction::CreateEventArgs {
+            event_id: self.event_id,
+            resolve_authority: payer.pubkey(),
+            resolvers: self.resolvers.clone(),
+            resolution_threshold: self.resolution_threshold,
+            dispute_window: self.dispute_window,
+            proposal_bond: self.proposal_bond,
+            oracle: self.oracle,
+            strike: self.strike,
+            price_expo: self.price_expo,
+            max_staleness: self.max_staleness,
+            max_confidence_bps: self.max_confidence_bps,
+            price_per_share: self.price_per_share,
+            pricing_mode: self.pricing_mode,
+            liquidity: self.liquidity,
+            creator_fee_bps: self.creator_fee_bps,
+            outcome_labels: self.outcome_labels.clone(),
+            event_type: self.event_type,
+            lower_bound: self.lower_bound,
+            upper_bound: self.upper_bound,
+            open_time: self.open_time,
+            close_time: self.close_time,
+            metadata: self.metadata.clone()
+        };
 
-        // create yes mint
-        create_mint(
-            banks_client,
-            payer,
-            recent_blockhash,
-            &self.yes_mint,
-            mint_rent,
-            &self.authority,
-        ).await.unwrap();
+        // the creator pays for the accounts of the event
+        let instructions = [
+            system_instruction::transfer(&payer.pubkey(), &self.creator.pubkey(), 100_000_000),
+            instruction::create_event(
+                &id(),
+                &self.creator.pubkey(),
+                &spl_token::id(),
+                self.collateral_mint.as_ref(),
+                create_event_args,
+            ).unwrap(),
+        ];
+        let mut transaction = Transaction::new_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+        );
 
-        // create no mint
-        create_mint(
-            banks_client,
-            payer,
-            recent_blockhash,
-            &self.no_mint,
-            mint_rent,
-            &self.authority,
-        ).await.unwrap();
+        transaction.sign(&[payer, &self.creator], *recent_blockhash);
+        context.banks_client.process_transaction(transaction).await?;
+        Ok(())
     }
 
-    #[allow(clippy::too_many_arguments)]
     pub async fn prepare_accounts_for_purchase(
         &self,
         banks_client: &mut BanksClient,
         payer: &Keypair,
         recent_blockhash: &Hash,
         user_token_account: &Keypair,
-        payment_account: &Keypair,
-        cost: u64
+        side_index: u8
     ) {
-        let rent = banks_client.get_rent().await.unwrap();
-        let token_account_rent = rent.minimum_balance(spl_token::state::Account::LEN);
+        let mint = &self.outcome_mints[side_index as usize];
 
         // create user token account
-        create_account(
+        create_token_account(
             banks_client,
             payer,
             recent_blockhash,
             user_token_account,
-            token_account_rent,
-            spl_token::state::Account::LEN as u64,
+            mint,
             &payer.pubkey(),
         ).await.unwrap();
+    }
 
-        // create payment account
-        create_account(
+    #[allow(clippy::too_many_arguments)]
+    pub async fn purchase_shares(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        user: &Keypair,
+        user_token_account: &Keypair,
+        side_index: u8,
+        num_tokens: u64
+    ) {
+        self.try_purchase_shares(
             banks_client,
             payer,
             recent_blockhash,
-            payment_account,
-            cost,
-            0,
-            &id(),
+            user,
+            user_token_account,
+            side_index,
+            num_tokens,
+            u64::MAX
         ).await.unwrap();
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub async fn purchase_shares(
+    pub async fn try_purchase_shares(
         &self,
         banks_client: &mut BanksClient,
         payer: &Keypair,
         recent_blockhash: &Hash,
+        user: &Keypair,
         user_token_account: &Keypair,
-        payment_account: &Keypair,
         side_index: u8,
-        num_tokens: u64
-    ) {
+        num_tokens: u64,
+        max_cost: u64
+    ) -> Result<(), TransportError> {
         let purchase_shares_args = instruction::PurchaseSharesArgs {
             side_index,
-            num_tokens
+            num_tokens,
+            max_cost
         };
 
         let mut transaction = Transaction::new_with_payer(
             &[instruction::purchase_shares(
                 &id(),
-                &payer.pubkey(),
+                &user.pubkey(),
                 &self.authority,
-                &self.event_account.pubkey(),
-                &self.yes_mint.pubkey(),
-                &self.no_mint.pubkey(),
+                &self.event_account,
+                &self.outcome_mints[side_index as usize],
                 &user_token_account.pubkey(),
-                &payment_account.pubkey(),
                 &spl_token::id(),
+                &self.fee_vault(),
+                None,
                 purchase_shares_args,
             ).unwrap()],
             Some(&payer.pubkey()),
         );
 
-        transaction.sign(&[payer], *recent_blockhash);
-        banks_client.process_transaction(transaction).await.unwrap();
+        transaction.sign(&[payer, user], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
     }
-}
 
-pub async fn create_mint(
-    banks_client: &mut BanksClient,
-    payer: &Keypair,
-    recent_blockhash: &Hash,
-    mint_account: &Keypair,
-    mint_rent: u64,
-    owner: &Pubkey,
-) -> Result<(), TransportError> {
-    let mut transaction = Transaction::new_with_payer(
-        &[
-            system_instruction::create_account(
-                &payer.pubkey(),
-                &mint_account.pubkey(),
-                mint_rent,
-                spl_token::state::Mint::LEN as u64,
-                &spl_token::id(),
-            ),
-            spl_token::instruction::initialize_mint(
-                &spl_token::id(),
-                &mint_account.pubkey(),
-                &owner,
-                None,
-                0,
-            )
-            .unwrap(),
-        ],
-        Some(&payer.pubkey()),
-    );
-    transaction.sign(&[payer, mint_account], *recent_blockhash);
-    banks_client.process_transaction(transaction).await?;
-    Ok(())
-}
+    pub async fn resolve_event(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        resolve_authority: &Keypair,
+        winning_side: u8
+    ) -> Result<(), TransportError> {
+        let resolve_event_args = instruction::ResolveEventArgs {
+            winning_side
+        };
 
-pub async fn create_account(
-    banks_client: &mut BanksClient,
-    payer: &Keypair,
-    recent_blockhash: &Hash,
-    account: &Keypair,
-    rent: u64,
-    space: u64,
-    owner: &Pubkey,
-) -> Result<(), TransportError> {
-    let mut transaction = Transaction::new_with_payer(
-        &[system_instruction::create_account(
-            &payer.pubkey(),
-            &account.pubkey(),
-            rent,
-            space,
-            owner,
-        )],
-        Some(&payer.pubkey()),
-    );
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::resolve_event(
+                &id(),
+                &resolve_authority.pubkey(),
+                &self.event_account,
+                resolve_event_args,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
 
-    transaction.sign(&[payer, account], *recent_blockhash);
-    banks_client.process_transaction(transaction).await?;
-    Ok(())
-}
+        transaction.sign(&[payer, resolve_authority], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
 
-pub async fn get_token_balance(banks_client: &mut BanksClient, token: &Pubkey) -> u64 {
-    let token_account = banks_client.get_account(*token).await.unwrap().unwrap();
-    let account_info: spl_token::state::Account =
-        spl_token::state::Account::unpack_from_slice(token_account.data.as_slice()).unwrap();
-    account_info.amount
-}
+    pub async fn resolve_scalar_event(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        resolve_authority: &Keypair,
+        value: i64
+    ) -> Result<(), TransportError> {
+        let resolve_scalar_event_args = instruction::ResolveScalarEventArgs {
+            value
+        };
 
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::resolve_scalar_event(
+                &id(),
+                &resolve_authority.pubkey(),
+                &self.event_account,
+                resolve_scalar_event_args,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
 
-#[tokio::test]
-async fn test_purchase_shares() {
-    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+        transaction.sign(&[payer, resolve_authority], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
 
-    let side_index = 0;// YES
-    let num_tokens = 17;
-    let cost = 50_000_000;// share price * num shares
+    #[allow(clippy::too_many_arguments)]
+    pub async fn redeem_shares(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        user: &Keypair,
+        user_token_account: &Pubkey,
+        mint: &Pubkey,
+        num_tokens: u64
+    ) -> Result<(), TransportError> {
+        let redeem_shares_args = instruction::RedeemSharesArgs {
+            num_tokens
+        };
 
-    let event = TestEvent::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                spl_token::instruction::approve(
+                    &spl_token::id(),
+                    user_token_account,
+                    &self.authority,
+                    &user.pubkey(),
+                    &[],
+                    num_tokens,
+                ).unwrap(),
+                instruction::redeem_shares(
+                    &id(),
+                    &user.pubkey(),
+                    &self.authority,
+                    &self.event_account,
+                    mint,
+                    user_token_account,
+                    &spl_token::id(),
+                    None,
+                    redeem_shares_args,
+                ).unwrap(),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, user], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
 
-    event.init_event(&mut banks_client, &payer, &recent_blockhash).await;
+    #[allow(clippy::too_many_arguments)]
+    pub async fn sell_shares(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        user: &Keypair,
+        user_token_account: &Pubkey,
+        side_index: u8,
+        num_tokens: u64,
+        min_proceeds: u64
+    ) -> Result<(), TransportError> {
+        let mint = &self.outcome_mints[side_index as usize];
+        let sell_shares_args = instruction::SellSharesArgs {
+            side_index,
+            num_tokens,
+            min_proceeds
+        };
 
-    let user_token_account = Keypair::new();
-    let payment_account = Keypair::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                spl_token::instruction::approve(
+                    &spl_token::id(),
+                    user_token_account,
+                    &self.authority,
+                    &user.pubkey(),
+                    &[],
+                    num_tokens,
+                ).unwrap(),
+                instruction::sell_shares(
+                    &id(),
+                    &user.pubkey(),
+                    &self.authority,
+                    &self.event_account,
+                    mint,
+                    user_token_account,
+                    &spl_token::id(),
+                    &self.fee_vault(),
+                    None,
+                    sell_shares_args,
+                ).unwrap(),
+            ],
+            Some(&payer.pubkey()),
+        );
 
-    event.prepare_accounts_for_purchase(
-        &mut banks_client,
-        &payer,
-        &recent_blockhash,
-        &user_token_account,
-        &payment_account,
-        cost
-    ).await;
+        transaction.sign(&[payer, user], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
 
-    // check payment account balance before purchase
-    let mut actual_payment_account = banks_client.get_account(payment_account.pubkey()).await.unwrap().unwrap();
-    assert_eq!(actual_payment_account.lamports, cost);
+    pub async fn void_event(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        resolve_authority: &Keypair
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::void_event(
+                &id(),
+                &resolve_authority.pubkey(),
+                &self.event_account,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
 
-    // get event account balance before purchase
-    let mut actual_event_account = banks_client.get_account(event.event_account.pubkey()).await.unwrap().unwrap();
-    let event_balance_before = actual_event_account.lamports;
+        transaction.sign(&[payer, resolve_authority], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
 
-    // purchase shares
-    event.purchase_shares(
-        &mut banks_client,
-        &payer,
-        &recent_blockhash,
-        &user_token_account,
-        &payment_account,
-        side_index,
-        num_tokens
-    ).await;
+    #[allow(clippy::too_many_arguments)]
+    pub async fn claim_refund(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        user: &Keypair,
+        user_token_account: &Pubkey,
+        mint: &Pubkey,
+        num_tokens: u64
+    ) -> Result<(), TransportError> {
+        let claim_refund_args = instruction::ClaimRefundArgs {
+            num_tokens
+        };
 
-    // check payment account balance after purchase
-    actual_payment_account = banks_client.get_account(payment_account.pubkey()).await.unwrap().unwrap();
-    assert_eq!(actual_payment_account.lamports, 0);
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                spl_token::instruction::approve(
+                    &spl_token::id(),
+                    user_token_account,
+                    &self.authority,
+                    &user.pubkey(),
+                    &[],
+                    num_tokens,
+                ).unwrap(),
+                instruction::claim_refund(
+                    &id(),
+                    &user.pubkey(),
+                    &self.authority,
+                    &self.event_account,
+                    mint,
+                    user_token_account,
+                    &spl_token::id(),
+                    None,
+                    claim_refund_args,
+                ).unwrap(),
+            ],
+            Some(&payer.pubkey()),
+        );
 
-    // check event account balance after purchase
-    actual_event_account = banks_client.get_account(event.event_account.pubkey()).await.unwrap().unwrap();
-    assert_eq!(actual_event_account.lamports, event_balance_before + cost);
+        transaction.sign(&[payer, user], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
 
-    // Check if user has correct number of tokens (YES)
-    let user_yes_tokens = get_token_balance(&mut banks_client, &user_token_account.pubkey()).await;
-    assert_eq!(user_yes_tokens, num_tokens);
+    pub async fn set_event_metadata(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        creator: &Keypair,
+        metadata: instruction::EventMetadataArgs
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::set_event_metadata(
+                &id(),
+                &creator.pubkey(),
+                &self.event_account,
+                &self.metadata_account,
+                metadata,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, creator], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn get_metadata(&self, banks_client: &mut BanksClient) -> state::EventMetadata {
+        let metadata_account = banks_client.get_account(self.metadata_account).await.unwrap().unwrap();
+        state::EventMetadata::decode(&metadata_account.data).unwrap()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn mint_complete_set(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        user_token_accounts: &[Pubkey],
+        num_sets: u64
+    ) -> Result<(), TransportError> {
+        let mint_complete_set_args = instruction::MintCompleteSetArgs {
+            num_sets
+        };
+
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::mint_complete_set(
+                &id(),
+                &payer.pubkey(),
+                &self.authority,
+                &self.event_account,
+                &self.outcome_mints,
+                user_token_accounts,
+                &spl_token::id(),
+                None,
+                mint_complete_set_args,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn merge_complete_set(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        user: &Keypair,
+        user_token_accounts: &[Pubkey],
+        num_sets: u64
+    ) -> Result<(), TransportError> {
+        let merge_complete_set_args = instruction::MergeCompleteSetArgs {
+            num_sets
+        };
+
+        let mut instructions = vec![];
+        for token_account in user_token_accounts {
+            instructions.push(spl_token::instruction::approve(
+                &spl_token::id(),
+                token_account,
+                &self.authority,
+                &user.pubkey(),
+                &[],
+                num_sets,
+            ).unwrap());
+        }
+        instructions.push(instruction::merge_complete_set(
+            &id(),
+            &user.pubkey(),
+            &self.authority,
+            &self.event_account,
+            &self.outcome_mints,
+            user_token_accounts,
+            &spl_token::id(),
+            None,
+            merge_complete_set_args,
+        ).unwrap());
+
+        let mut transaction = Transaction::new_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, user], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn withdraw_fees(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        recipient: &Keypair,
+        destination: Option<&Pubkey>
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::withdraw_fees(
+                &id(),
+                &recipient.pubkey(),
+                &self.authority,
+                &self.event_account,
+                &self.fee_vault(),
+                &spl_token::id(),
+                destination,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, recipient], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn vote_resolution(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        resolver: &Keypair,
+        resolution: state::Resolution
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::vote_resolution(
+                &id(),
+                &resolver.pubkey(),
+                &self.event_account,
+                &self.resolution(),
+                instruction::VoteResolutionArgs { resolution },
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, resolver], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn propose_resolution(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        proposer: &Keypair,
+        resolution: state::Resolution
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::propose_resolution(
+                &id(),
+                &proposer.pubkey(),
+                &self.event_account,
+                instruction::ProposeResolutionArgs { resolution },
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, proposer], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn dispute_resolution(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        disputer: &Keypair
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::dispute_resolution(
+                &id(),
+                &disputer.pubkey(),
+                &self.event_account,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, disputer], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn finalize_resolution(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        proposer: &Pubkey
+    ) -> Result<(), TransportError> {
+        let instruction = instruction::finalize_resolution(
+            &id(),
+            proposer,
+            &self.event_account,
+        ).unwrap();
+        process_instruction(banks_client, payer, recent_blockhash, instruction).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn settle_dispute(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        resolve_authority: &Keypair,
+        proposer: &Pubkey,
+        disputer: &Pubkey,
+        resolution: state::Resolution
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::settle_dispute(
+                &id(),
+                &resolve_authority.pubkey(),
+                &self.event_account,
+                proposer,
+                disputer,
+                instruction::SettleDisputeArgs { resolution },
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, resolve_authority], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn resolve_from_oracle(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        oracle: &Pubkey
+    ) -> Result<(), TransportError> {
+        let instruction = instruction::resolve_from_oracle(
+            &id(),
+            &self.event_account,
+            oracle,
+        ).unwrap();
+        process_instruction(banks_client, payer, recent_blockhash, instruction).await
+    }
+
+    pub async fn propose_resolve_authority(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        resolve_authority: &Keypair,
+        new_resolve_authority: &Pubkey
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::propose_resolve_authority(
+                &id(),
+                &resolve_authority.pubkey(),
+                &self.event_account,
+                instruction::ProposeResolveAuthorityArgs {
+                    new_resolve_authority: *new_resolve_authority
+                },
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, resolve_authority], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+
+    pub async fn accept_resolve_authority(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        pending_resolve_authority: &Keypair
+    ) -> Result<(), TransportError> {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::accept_resolve_authority(
+                &id(),
+                &pending_resolve_authority.pubkey(),
+                &self.event_account,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer, pending_resolve_authority], *recent_blockhash);
+        banks_client.process_transaction(transaction).await?;
+        Ok(())
+    }
+}
+
+pub async fn create_mint(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    mint_account: &Keypair,
+    mint_rent: u64,
+    owner: &Pubkey,
+) -> Result<(), TransportError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint_account.pubkey(),
+                mint_rent,
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint_account.pubkey(),
+                owner,
+                None,
+                0,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, mint_account], *recent_blockhash);
+    banks_client.process_transaction(transaction).await?;
+    Ok(())
+}
+
+pub async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    account: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<(), TransportError> {
+    let rent = banks_client.get_rent().await.unwrap();
+    let account_rent = rent.minimum_balance(spl_token::state::Account::LEN);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                account_rent,
+                spl_token::state::Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, account], *recent_blockhash);
+    banks_client.process_transaction(transaction).await?;
+    Ok(())
+}
+
+pub async fn create_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    account: &Keypair,
+    rent: u64,
+    space: u64,
+    owner: &Pubkey,
+) -> Result<(), TransportError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            rent,
+            space,
+            owner,
+        )],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(&[payer, account], *recent_blockhash);
+    banks_client.process_transaction(transaction).await?;
+    Ok(())
+}
+
+pub async fn get_token_balance(banks_client: &mut BanksClient, token: &Pubkey) -> u64 {
+    let token_account = banks_client.get_account(*token).await.unwrap().unwrap();
+    let account_info: spl_token::state::Account =
+        spl_token::state::Account::unpack_from_slice(token_account.data.as_slice()).unwrap();
+    account_info.amount
+}
+
+pub async fn process_instruction(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    instruction: Instruction,
+) -> Result<(), TransportError> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[payer], *recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+/// Moves the clock forward by `seconds` and returns a new blockhash. Banks of
+/// the test validator have no vote timestamps and keep the clock they inherit,
+/// so the clock is stored ahead before warping to a new bank.
+pub async fn advance_clock(context: &mut ProgramTestContext, seconds: i64) -> Hash {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += seconds;
+    context.set_account(
+        &sysvar::clock::id(),
+        &sdk_account::create_account_shared_data_for_test(&clock),
+    );
+    context.warp_to_slot(clock.slot + 2).unwrap();
+    context.banks_client.get_recent_blockhash().await.unwrap()
+}
+
+/// Pyth-style price account publishing an aggregate price
+pub fn price_account(price: i64, conf: u64, expo: i32, status: u32, timestamp: i64) -> Account {
+    let mut data = vec![0; 3312];
+    let mut write = |offset: usize, bytes: &[u8]| {
+        data[offset..offset + bytes.len()].copy_from_slice(bytes)
+    };
+    write(oracle::MAGIC_OFFSET, &oracle::MAGIC.to_le_bytes());
+    write(oracle::VERSION_OFFSET, &oracle::VERSION.to_le_bytes());
+    write(oracle::ACCOUNT_TYPE_OFFSET, &oracle::ACCOUNT_TYPE_PRICE.to_le_bytes());
+    write(oracle::EXPO_OFFSET, &expo.to_le_bytes());
+    write(oracle::TIMESTAMP_OFFSET, &timestamp.to_le_bytes());
+    write(oracle::PRICE_OFFSET, &price.to_le_bytes());
+    write(oracle::CONF_OFFSET, &conf.to_le_bytes());
+    write(oracle::STATUS_OFFSET, &status.to_le_bytes());
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: oracle_program(),
+        ..Account::default()
+    }
+}
+
+pub async fn initialize_config(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    protocol_fee_bps: u16,
+    protocol_fee_recipient: &Pubkey,
+) -> Result<(), TransportError> {
+    let config_args = instruction::ConfigArgs {
+        admin: payer.pubkey(),
+        protocol_fee_bps,
+        protocol_fee_recipient: *protocol_fee_recipient,
+        oracle_program: oracle_program()
+    };
+    let upgrade_authority = upgrade_authority();
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::initialize_config(&id(), &upgrade_authority.pubkey(), config_args).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, &upgrade_authority], *recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+pub async fn set_paused(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    admin: &Keypair,
+    paused: bool,
+) -> Result<(), TransportError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::set_paused(
+            &id(),
+            &admin.pubkey(),
+            instruction::SetPausedArgs { paused },
+        ).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, admin], *recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+pub fn assert_program_error(result: Result<(), TransportError>, expected: error::PredictChainError) {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, expected as u32),
+        other => panic!("expected {:?}, got {:?}", expected, other),
+    }
+}
+
+
+#[tokio::test]
+async fn test_create_event() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent::new();
+
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // check event state
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    assert_eq!(event_account.owner, id());
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.bump_seed, event.bump_seed);
+    assert_eq!(event_data.resolve_authority, payer.pubkey());
+    assert_eq!(event_data.outcomes.len(), 2);
+    for (outcome, (label, mint)) in event_data.outcomes.iter().zip(["YES", "NO"].iter().zip(&event.outcome_mints)) {
+        assert_eq!(outcome.mint_address, *mint);
+        assert_eq!(outcome.label, *label);
+    }
+    assert_eq!(event_data.volume, 0);
+    assert_eq!(event_data.price_per_share, event.price_per_share);
+    assert_eq!(event_data.status, state::EventStatus::Open);
+
+    // check both mints are controlled by the event authority
+    for mint in &event.outcome_mints {
+        let mint_account = banks_client.get_account(*mint).await.unwrap().unwrap();
+        let mint_data = spl_token::state::Mint::unpack(&mint_account.data).unwrap();
+        assert_eq!(mint_data.mint_authority, COption::Some(event.authority));
+        assert_eq!(mint_data.supply, 0);
+    }
+
+    // event account can't be created twice
+    let result = TestEvent {
+        price_per_share: 2_000_000,
+        ..event
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_event_addresses() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    // addresses are found from the creator and the event id alone
+    let event = TestEvent {
+        event_id: 7,
+        ..TestEvent::new()
+    };
+    let creator = event.creator.pubkey();
+    let (event_account, event_bump_seed) = address::find_event_address(&id(), &creator, 7);
+    let event = TestEvent {
+        event_account,
+        metadata_account: address::find_metadata_address(&id(), &event_account).0,
+        authority: address::find_authority_address(&id(), &event_account).0,
+        bump_seed: address::find_authority_address(&id(), &event_account).1,
+        outcome_mints: (0..2)
+            .map(|index| address::find_mint_address(&id(), &event_account, index).0)
+            .collect(),
+        ..event
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let account = banks_client.get_account(event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&account.data).unwrap();
+    assert_eq!(event_data.creator, creator);
+    assert_eq!(event_data.event_id, 7);
+    assert_eq!(event_data.bump_seed, event.bump_seed);
+    assert_eq!(event_data.event_bump_seed, event_bump_seed);
+    assert_eq!(event_data.metadata, event.metadata_account);
+    assert_eq!(event_data.fee_vault, address::find_fee_vault_address(&id(), &event_account).0);
+    assert_eq!(event_data.outcomes[0].mint_address, event.outcome_mints[0]);
+    assert_eq!(event_data.outcomes[1].mint_address, event.outcome_mints[1]);
+
+    // funding the addresses of an event id beforehand doesn't block it, the
+    // program topping them up to rent exemption
+    let event = TestEvent::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &event.event_account, 1),
+            system_instruction::transfer(&payer.pubkey(), &event.outcome_mints[0], 1),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let event_len = state::EventAccount::packed_len(&event.outcome_labels);
+    let account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    assert_eq!(account.lamports, Rent::default().minimum_balance(event_len));
+    let mint = banks_client.get_account(event.outcome_mints[0]).await.unwrap().unwrap();
+    assert_eq!(mint.lamports, Rent::default().minimum_balance(spl_token::state::Mint::LEN));
+
+    // accounts of one event id can't be initialized for another
+    let event = TestEvent::new();
+    event.preload_accounts(&mut context).await;
+    let create_event_args = |event_id| instruction::CreateEventArgs {
+        event_id,
+        resolve_authority: payer.pubkey(),
+        resolvers: vec![],
+        resolution_threshold: 0,
+        dispute_window: 0,
+        proposal_bond: 0,
+        oracle: Pubkey::default(),
+        strike: 0,
+        price_expo: 0,
+        max_staleness: 0,
+        max_confidence_bps: 0,
+        price_per_share: 1_000_000,
+        pricing_mode: state::PricingMode::FixedPrice,
+        liquidity: 0,
+        creator_fee_bps: 0,
+        outcome_labels: vec!["YES".to_string(), "NO".to_string()],
+        event_type: state::EventType::Categorical,
+        lower_bound: 0,
+        upper_bound: 0,
+        open_time: 0,
+        close_time: i64::MAX,
+        metadata: instruction::EventMetadataArgs::default()
+    };
+    let mut create_event = instruction::create_event(
+        &id(),
+        &event.creator.pubkey(),
+        &spl_token::id(),
+        None,
+        create_event_args(1),
+    ).unwrap();
+    create_event.data = instruction::create_event(
+        &id(),
+        &event.creator.pubkey(),
+        &spl_token::id(),
+        None,
+        create_event_args(2),
+    ).unwrap().data;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &event.creator.pubkey(), 100_000_000),
+            create_event,
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &event.creator], recent_blockhash);
+    assert_program_error(
+        banks_client.process_transaction(transaction).await,
+        error::PredictChainError::WrongAccountAddress,
+    );
+}
+
+#[tokio::test]
+async fn test_event_metadata() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let metadata = instruction::EventMetadataArgs {
+        title: "Lakers vs Celtics".to_string(),
+        description: "Winner of the game, overtime included".to_string(),
+        league: "NBA".to_string(),
+        category: "Sports".to_string(),
+        image0: "https://example.com/lakers.png".to_string(),
+        image1: "https://example.com/celtics.png".to_string(),
+    };
+
+    // strings are bounded
+    let result = TestEvent {
+        metadata: instruction::EventMetadataArgs {
+            title: "x".repeat(state::EventMetadata::MAX_TITLE_LEN + 1),
+            ..metadata.clone()
+        },
+        ..TestEvent::new()
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidMetadata);
+
+    let event = TestEvent {
+        open_time: i64::MAX - 1,
+        metadata: metadata.clone(),
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.creator, event.creator.pubkey());
+    assert_eq!(event_data.metadata, event.metadata_account);
+
+    let stored = event.get_metadata(&mut banks_client).await;
+    assert_eq!(stored.event, event.event_account);
+    assert_eq!(stored.title, metadata.title);
+    assert_eq!(stored.description, metadata.description);
+    assert_eq!(stored.league, metadata.league);
+    assert_eq!(stored.category, metadata.category);
+    assert_eq!(stored.image0, metadata.image0);
+    assert_eq!(stored.image1, metadata.image1);
+
+    // only the creator can edit the metadata
+    let not_creator = Keypair::new();
+    let result = event.set_event_metadata(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &not_creator,
+        metadata.clone()
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongCreator);
+
+    // edited strings can be shorter than the ones they replace
+    let edited = instruction::EventMetadataArgs {
+        title: "LAL @ BOS".to_string(),
+        description: String::new(),
+        ..metadata.clone()
+    };
+    event.set_event_metadata(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &event.creator,
+        edited.clone()
+    ).await.unwrap();
+    let stored = event.get_metadata(&mut banks_client).await;
+    assert_eq!(stored.title, edited.title);
+    assert_eq!(stored.description, "");
+    assert_eq!(stored.league, edited.league);
+
+    let result = event.set_event_metadata(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &event.creator,
+        instruction::EventMetadataArgs {
+            image0: "x".repeat(state::EventMetadata::MAX_URI_LEN + 1),
+            ..edited.clone()
+        }
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidMetadata);
+
+    // a metadata account can only be edited for its own event
+    let other_event = TestEvent {
+        open_time: i64::MAX - 1,
+        ..TestEvent::new()
+    };
+    other_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let mut metadata_account = banks_client.get_account(other_event.metadata_account).await.unwrap().unwrap();
+    metadata_account.data = banks_client.get_account(event.metadata_account).await.unwrap().unwrap().data;
+    context.set_account(&other_event.metadata_account, &metadata_account.into());
+    let result = other_event.set_event_metadata(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &other_event.creator,
+        edited.clone()
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongAccountAddress);
+
+    // metadata is frozen once trading is open
+    let event = TestEvent {
+        metadata: metadata.clone(),
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let result = event.set_event_metadata(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &event.creator,
+        edited
+    ).await;
+    assert_program_error(result, error::PredictChainError::MetadataLocked);
+    assert_eq!(event.get_metadata(&mut banks_client).await.title, metadata.title);
+}
+
+#[tokio::test]
+async fn test_purchase_shares() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let side_index = 0;// YES
+    let num_tokens = 17;
+
+    let event = TestEvent::new();
+    let cost = event.price_per_share * num_tokens;
+
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let user = Keypair::new();
+    let user_token_account = Keypair::new();
+
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        side_index
+    ).await;
+
+    // fund user with exactly the cost
+    create_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user,
+        cost,
+        0,
+        &system_program::id(),
+    ).await.unwrap();
+
+    // get event account balance before purchase
+    let mut actual_event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_balance_before = actual_event_account.lamports;
+
+    // purchase shares
+    event.purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user,
+        &user_token_account,
+        side_index,
+        num_tokens
+    ).await;
+
+    // check the cost was pulled from the user
+    assert_eq!(banks_client.get_balance(user.pubkey()).await.unwrap(), 0);
+
+    // check event account balance after purchase
+    actual_event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    assert_eq!(actual_event_account.lamports, event_balance_before + cost);
+
+    // Check if user has correct number of tokens (YES)
+    let user_yes_tokens = get_token_balance(&mut banks_client, &user_token_account.pubkey()).await;
+    assert_eq!(user_yes_tokens, num_tokens);
+}
+
+#[tokio::test]
+async fn test_purchase_shares_price() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent::new();
+
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // user must be able to pay num_tokens * price_per_share
+    let user = Keypair::new();
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    create_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user,
+        event.price_per_share * 10 - 1,
+        0,
+        &system_program::id(),
+    ).await.unwrap();
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user,
+        &user_token_account,
+        0,
+        10,
+        u64::MAX
+    ).await;
+    assert!(result.is_err());
+
+    // only the exact cost is pulled from the user
+    let user = Keypair::new();
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        1
+    ).await;
+    create_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user,
+        event.price_per_share * 10,
+        0,
+        &system_program::id(),
+    ).await.unwrap();
+    let event_balance_before = banks_client.get_balance(event.event_account).await.unwrap();
+    event.purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user,
+        &user_token_account,
+        1,
+        4
+    ).await;
+    assert_eq!(
+        banks_client.get_balance(user.pubkey()).await.unwrap(),
+        event.price_per_share * 6
+    );
+    assert_eq!(
+        banks_client.get_balance(event.event_account).await.unwrap(),
+        event_balance_before + event.price_per_share * 4
+    );
+    assert_eq!(get_token_balance(&mut banks_client, &user_token_account.pubkey()).await, 4);
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, event.price_per_share * 4);
+}
+
+#[test]
+fn test_constant_product_buy_cost() {
+    // balanced pool prices each side at about one half, rounded up
+    assert_eq!(pricing::constant_product_buy_cost(1_000_000, 1_000_000, 10), Some(6));
+    assert_eq!(pricing::constant_product_buy_cost(1_000_000, 1_000_000, 1_000), Some(501));
+
+    // invariant never decreases and a share never costs more than its value
+    for (reserve_in, reserve_out, amount) in
+        [(1_000, 1_000, 999), (1_000, 9_000, 5_000), (9_000, 1_000, 20_000), (7, 3, 1)]
+    {
+        let cost = pricing::constant_product_buy_cost(reserve_in, reserve_out, amount).unwrap();
+        let new_in = (reserve_in + cost - amount) as u128;
+        let new_out = (reserve_out + cost) as u128;
+        assert!(new_in * new_out >= reserve_in as u128 * reserve_out as u128);
+        assert!(cost <= amount);
+    }
+
+    // selling what was just bought returns its cost, minus rounding
+    let cost = pricing::constant_product_buy_cost(5_000, 8_000, 700).unwrap();
+    let proceeds = pricing::constant_product_sell_proceeds(5_000 + cost - 700, 8_000 + cost, 700).unwrap();
+    assert!(proceeds <= cost && cost - proceeds <= 1);
+
+    assert_eq!(pricing::constant_product_buy_cost(0, 1_000, 1), None);
+    assert_eq!(pricing::constant_product_buy_cost(u64::MAX, u64::MAX, u64::MAX), None);
+}
+
+/// Amounts spread over every magnitude, extremes included
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        prop::sample::select(vec![0, 1, 2, u32::MAX as u64, u64::MAX / 2, u64::MAX - 1, u64::MAX]),
+        (0..64u32).prop_flat_map(|shift| 0..=u64::MAX >> shift),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn test_checked_math(a in amount(), b in amount(), pool in amount()) {
+        let (a_wide, b_wide) = (a as u128, b as u128);
+        let fits = |value: u128| if value <= u64::MAX as u128 {
+            Ok(value as u64)
+        } else {
+            Err(error::PredictChainError::MathOverflow)
+        };
+
+        prop_assert_eq!(math::add(a, b), fits(a_wide + b_wide));
+        prop_assert_eq!(math::mul(a, b), fits(a_wide * b_wide));
+        if a >= b {
+            prop_assert_eq!(math::sub(a, b), Ok(a - b));
+        } else {
+            prop_assert_eq!(math::sub(a, b), Err(error::PredictChainError::MathOverflow));
+        }
+
+        // a share of a pool never exceeds the pool
+        let (part, whole) = (a.min(b) as u128, a.max(b) as u128);
+        if whole > 0 {
+            let share = math::mul_div(part, pool as u128, whole).unwrap();
+            prop_assert!(share <= pool);
+            prop_assert_eq!(share as u128, part * pool as u128 / whole);
+        }
+        prop_assert_eq!(math::mul_div(a_wide, 1, 0), Err(error::PredictChainError::MathOverflow));
+    }
+}
+
+#[test]
+fn test_mul_div_bounds() {
+    assert_eq!(
+        math::mul_div(u64::MAX as u128, 2, 1),
+        Err(error::PredictChainError::MathOverflow)
+    );
+    assert_eq!(math::mul_div(u64::MAX as u128, u64::MAX as u128, u64::MAX as u128), Ok(u64::MAX));
+}
+
+#[test]
+fn test_lmsr_cost() {
+    // C(0, 0) = b * ln(2)
+    let cost = pricing::lmsr_cost(100, &[0, 0]).unwrap();
+    let expected = 69_314_718_055_994;
+    assert!(cost.max(expected) - cost.min(expected) < 1_000);
+
+    // a single outcome always costs its quantity
+    assert_eq!(pricing::lmsr_cost(100, &[42]), Some(42 * pricing::SCALE));
+
+    // balanced market prices each side at about one half
+    let cost = pricing::lmsr_buy_cost(1_000, &[0, 0], 0, 1, 1_000_000).unwrap();
+    assert!((500_124..=500_126).contains(&cost));
+
+    // buying one share of every outcome costs one full share
+    let yes_cost = pricing::lmsr_buy_cost(100, &[0, 0], 0, 10, 1_000_000).unwrap();
+    let no_cost = pricing::lmsr_buy_cost(100, &[10, 0], 1, 10, 1_000_000).unwrap();
+    assert!((10_000_000..=10_000_002).contains(&(yes_cost + no_cost)));
+
+    // extreme quantities stay bounded by the share value
+    let cost = pricing::lmsr_buy_cost(1, &[0, 1_000_000], 1, 1, 1_000_000).unwrap();
+    assert!(cost <= 1_000_000);
+    // selling what was just bought returns its cost, minus rounding
+    let cost = pricing::lmsr_buy_cost(50, &[7, 30], 0, 25, 1_000_000).unwrap();
+    let proceeds = pricing::lmsr_sell_proceeds(50, &[32, 30], 0, 25, 1_000_000).unwrap();
+    assert!(proceeds <= cost && cost - proceeds <= 1);
+    assert_eq!(pricing::lmsr_sell_proceeds(50, &[32, 30], 1, 31, 1_000_000), None);
+
+    assert_eq!(pricing::lmsr_buy_cost(100, &[0, 0], 2, 1, 1_000_000), None);
+    assert_eq!(pricing::lmsr_cost(0, &[0, 0]), None);
+}
+
+#[tokio::test]
+async fn test_purchase_shares_lmsr() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent {
+        pricing_mode: state::PricingMode::Lmsr,
+        liquidity: 100,
+        ..TestEvent::new()
+    };
+
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let first_cost = pricing::lmsr_buy_cost(100, &[0, 0], 0, 10, event.price_per_share).unwrap();
+    let second_cost = pricing::lmsr_buy_cost(100, &[10, 0], 0, 10, event.price_per_share).unwrap();
+    assert!((5_124_947..=5_124_949).contains(&first_cost));
+    assert!(second_cost > first_cost);
+
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10,
+        first_cost - 1
+    ).await;
+    assert_program_error(result, error::PredictChainError::SlippageExceeded);
+
+    for cost in [first_cost, second_cost] {
+        let event_balance_before = banks_client.get_balance(event.event_account).await.unwrap();
+        event.try_purchase_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            &user_token_account,
+            0,
+            10,
+            cost
+        ).await.unwrap();
+        assert_eq!(
+            banks_client.get_balance(event.event_account).await.unwrap(),
+            event_balance_before + cost
+        );
+    }
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.liquidity, 100);
+    assert_eq!(event_data.quantities(), vec![20, 0]);
+    assert_eq!(event_data.volume, first_cost + second_cost);
+}
+
+#[tokio::test]
+async fn test_purchase_shares_constant_product() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent {
+        pricing_mode: state::PricingMode::ConstantProduct,
+        liquidity: 100,
+        ..TestEvent::new()
+    };
+
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let reserve = event.price_per_share * event.liquidity;
+    let amount = event.price_per_share * 10;
+    let first_cost = pricing::constant_product_buy_cost(reserve, reserve, amount).unwrap();
+    let yes_reserve = reserve + first_cost - amount;
+    let no_reserve = reserve + first_cost;
+    let second_cost = pricing::constant_product_buy_cost(yes_reserve, no_reserve, amount).unwrap();
+    assert!(first_cost < amount);
+    assert!(second_cost > first_cost);
+
+    // trade is rejected when its cost exceeds max_cost
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10,
+        first_cost - 1
+    ).await;
+    assert_program_error(result, error::PredictChainError::SlippageExceeded);
+
+    // each purchase moves the YES price up
+    for (cost, max_cost) in [(first_cost, first_cost), (second_cost, second_cost + 1)] {
+        let event_balance_before = banks_client.get_balance(event.event_account).await.unwrap();
+        event.try_purchase_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            &user_token_account,
+            0,
+            10,
+            max_cost
+        ).await.unwrap();
+        assert_eq!(
+            banks_client.get_balance(event.event_account).await.unwrap(),
+            event_balance_before + cost
+        );
+    }
+    assert_eq!(get_token_balance(&mut banks_client, &user_token_account.pubkey()).await, 20);
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.outcomes[0].reserve, yes_reserve + second_cost - amount);
+    assert_eq!(event_data.outcomes[1].reserve, no_reserve + second_cost);
+    assert_eq!(event_data.volume, first_cost + second_cost);
+    assert!(event_data.outcomes[1].reserve > event_data.outcomes[0].reserve);
+}
+
+const EXTREME_PRICE_PER_SHARE: u64 = 1_000_000_000;
+
+#[tokio::test]
+async fn test_purchase_shares_extreme_amounts() {
+    let price_per_share = EXTREME_PRICE_PER_SHARE;
+    let purchased = purchase_shares(&[
+        1,
+        1_000,
+        u64::MAX / price_per_share,
+        u64::MAX / price_per_share + 1,
+        u64::MAX / 2,
+        u64::MAX,
+    ]).await;
+    assert!(purchased.iter().all(|&bought| bought > 0));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    // identical transactions are deduplicated by the bank, so each amount is bought once
+    #[test]
+    fn test_purchase_shares_arbitrary_amounts(
+        amounts in prop::collection::btree_set(amount().prop_filter("no shares", |n| *n > 0), 1..8)
+    ) {
+        let amounts: Vec<u64> = amounts.into_iter().collect();
+        tokio::runtime::Runtime::new().unwrap().block_on(purchase_shares(&amounts));
+    }
+}
+
+/// Buys each amount of shares of every pricing mode's event in turn, checking
+/// each purchase costs what it should or fails as it should, and returns the
+/// shares bought of each event
+async fn purchase_shares(amounts: &[u64]) -> Vec<u64> {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let price_per_share = EXTREME_PRICE_PER_SHARE;
+    let events = [
+        TestEvent { price_per_share, ..TestEvent::new() },
+        TestEvent {
+            price_per_share,
+            pricing_mode: state::PricingMode::ConstantProduct,
+            liquidity: 1_000,
+            ..TestEvent::new()
+        },
+        TestEvent {
+            price_per_share,
+            pricing_mode: state::PricingMode::Lmsr,
+            liquidity: 1_000,
+            ..TestEvent::new()
+        },
+    ];
+
+    let mut purchased = vec![];
+    for event in &events {
+        event.init_event(&mut context, &payer, &recent_blockhash).await;
+        let user_token_account = Keypair::new();
+        event.prepare_accounts_for_purchase(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &user_token_account,
+            0
+        ).await;
+
+        let mut bought = 0;
+        for &num_tokens in amounts {
+            let payer_lamports = banks_client.get_balance(payer.pubkey()).await.unwrap();
+            let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+            let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+            let result = event.try_purchase_shares(
+                &mut banks_client,
+                &payer,
+                &recent_blockhash,
+                &payer,
+                &user_token_account,
+                0,
+                num_tokens,
+                u64::MAX
+            ).await;
+
+            // shares worth more than a u64 can't be priced, and shares the
+            // market maker can't price exceed its liquidity
+            let value = num_tokens
+                .checked_mul(price_per_share)
+                .ok_or(error::PredictChainError::MathOverflow);
+            let cost = match event.pricing_mode {
+                state::PricingMode::FixedPrice => value,
+                state::PricingMode::ConstantProduct => value.and_then(|amount| {
+                    let (reserve_in, reserve_out) =
+                        (event_data.outcomes[0].reserve, event_data.outcomes[1].reserve);
+                    pricing::constant_product_buy_cost(reserve_in, reserve_out, amount)
+                        .filter(|cost| reserve_in.checked_add(*cost).map_or(false, |reserve| reserve > amount))
+                        .ok_or(error::PredictChainError::InsufficientLiquidity)
+                }),
+                state::PricingMode::Lmsr => pricing::lmsr_buy_cost(
+                    event.liquidity,
+                    &event_data.quantities(),
+                    0,
+                    num_tokens,
+                    price_per_share,
+                ).ok_or(error::PredictChainError::InsufficientLiquidity),
+            };
+            // the pool can't hold more than a u64 of volume
+            match cost.and_then(|cost| math::add(event_data.volume, cost).map(|_| cost)) {
+                Err(expected) => assert_program_error(result, expected),
+                // shares that can be priced only fail when the payer can't afford them
+                Ok(cost) if cost > payer_lamports => assert_eq!(
+                    result.unwrap_err().unwrap(),
+                    TransactionError::InstructionError(
+                        0,
+                        InstructionError::Custom(SystemError::ResultWithNegativeLamports as u32)
+                    )
+                ),
+                Ok(_) => {
+                    result.unwrap();
+                    bought += num_tokens;
+                }
+            }
+
+            // the pool always holds exactly the collected volume
+            let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+            let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+            assert_eq!(
+                event_account.lamports - rent.minimum_balance(event_account.data.len()),
+                event_data.volume
+            );
+            let collateral: u64 = event_data.outcomes.iter().map(|outcome| outcome.collateral).sum();
+            assert_eq!(collateral, event_data.volume);
+            assert_eq!(get_token_balance(&mut banks_client, &user_token_account.pubkey()).await, bought);
+        }
+        purchased.push(bought);
+
+        // shares that were never bought can't be sold, however many
+        let result = event.sell_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            &user_token_account.pubkey(),
+            0,
+            u64::MAX,
+            0
+        ).await;
+        assert!(result.is_err());
+    }
+    purchased
+}
+
+#[tokio::test]
+async fn test_extreme_collateral_volume() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let collateral_mint = Keypair::new();
+    create_mint(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &collateral_mint,
+        rent.minimum_balance(spl_token::state::Mint::LEN),
+        &payer.pubkey(),
+    ).await.unwrap();
+
+    let event = TestEvent {
+        price_per_share: 1,
+        collateral_mint: Some(collateral_mint.pubkey()),
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // the whole supply of collateral tokens can't push the volume past u64::MAX
+    let collateral_account = Keypair::new();
+    let yes_token_account = Keypair::new();
+    let no_token_account = Keypair::new();
+    for (token_account, mint) in [
+        (&collateral_account, collateral_mint.pubkey()),
+        (&yes_token_account, event.outcome_mints[0]),
+        (&no_token_account, event.outcome_mints[1]),
+    ] {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &mint,
+            &payer.pubkey(),
+        ).await.unwrap();
+    }
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &collateral_mint.pubkey(),
+            &collateral_account.pubkey(),
+            &payer.pubkey(),
+            &[],
+            u64::MAX,
+        ).unwrap()
+    ).await.unwrap();
+
+    let half = 1 << 63;
+    let purchase_shares = |side_index: u8, token_account: &Keypair, num_tokens: u64| {
+        instruction::purchase_shares(
+            &id(),
+            &payer.pubkey(),
+            &event.authority,
+            &event.event_account,
+            &event.outcome_mints[side_index as usize],
+            &token_account.pubkey(),
+            &spl_token::id(),
+            &event.fee_vault(),
+            Some((&event.collateral_vault, &collateral_account.pubkey())),
+            instruction::PurchaseSharesArgs {
+                side_index,
+                num_tokens,
+                max_cost: u64::MAX
+            },
+        ).unwrap()
+    };
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        purchase_shares(0, &yes_token_account, half)
+    ).await.unwrap();
+    let result = process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        purchase_shares(1, &no_token_account, half)
+    ).await;
+    assert_program_error(result, error::PredictChainError::MathOverflow);
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        purchase_shares(1, &no_token_account, half - 1)
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, u64::MAX);
+    assert_eq!(get_token_balance(&mut banks_client, &event.collateral_vault).await, u64::MAX);
+    assert_eq!(get_token_balance(&mut banks_client, &collateral_account.pubkey()).await, 0);
+
+    // the whole pool is redeemed by the winning side without losing a token
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await.unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            spl_token::instruction::approve(
+                &spl_token::id(),
+                &yes_token_account.pubkey(),
+                &event.authority,
+                &payer.pubkey(),
+                &[],
+                half,
+            ).unwrap(),
+            instruction::redeem_shares(
+                &id(),
+                &payer.pubkey(),
+                &event.authority,
+                &event.event_account,
+                &event.outcome_mints[0],
+                &yes_token_account.pubkey(),
+                &spl_token::id(),
+                Some((&event.collateral_vault, &collateral_account.pubkey())),
+                instruction::RedeemSharesArgs { num_tokens: half },
+            ).unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &collateral_account.pubkey()).await, u64::MAX);
+    assert_eq!(get_token_balance(&mut banks_client, &event.collateral_vault).await, 0);
+}
+
+#[tokio::test]
+async fn test_purchase_shares_account_substitutions() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let event = TestEvent::new();
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let other_event = TestEvent::new();
+    other_event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+
+    // a mint outside the event whose authority happens to be the event authority
+    let foreign_mint = Keypair::new();
+    create_mint(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &foreign_mint,
+        rent.minimum_balance(spl_token::state::Mint::LEN),
+        &event.authority,
+    ).await.unwrap();
+    let foreign_token_account = Keypair::new();
+    create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &foreign_token_account,
+        &foreign_mint.pubkey(),
+        &payer.pubkey(),
+    ).await.unwrap();
+
+    let purchase_shares = || instruction::purchase_shares(
+        &id(),
+        &payer.pubkey(),
+        &event.authority,
+        &event.event_account,
+        &event.outcome_mints[0],
+        &user_token_account.pubkey(),
+        &spl_token::id(),
+        &event.fee_vault(),
+        None,
+        instruction::PurchaseSharesArgs {
+            side_index: 0,
+            num_tokens: 10,
+            max_cost: u64::MAX
+        },
+    ).unwrap();
+
+    let substitutions = [
+        (1, other_event.authority, error::PredictChainError::InvalidAuthorityAccount),
+        (2, payer.pubkey(), error::PredictChainError::WrongOwner),
+        (3, foreign_mint.pubkey(), error::PredictChainError::InvalidTokenMint),
+        (3, event.outcome_mints[1], error::PredictChainError::InvalidTokenMint),
+        (3, other_event.outcome_mints[0], error::PredictChainError::InvalidTokenMint),
+        (4, foreign_token_account.pubkey(), error::PredictChainError::InvalidTokenMint),
+        (5, spl_token::id(), error::PredictChainError::InvalidSystemProgram),
+        (6, system_program::id(), error::PredictChainError::InvalidTokenProgram),
+        (7, other_event.fee_vault(), error::PredictChainError::WrongAccountAddress),
+    ];
+    for (index, substitute, expected) in substitutions {
+        let mut instruction = purchase_shares();
+        instruction.accounts[index].pubkey = substitute;
+        let result = process_instruction(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            instruction
+        ).await;
+        assert_program_error(result, expected);
+    }
+
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        purchase_shares()
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &user_token_account.pubkey()).await, 10);
+    assert_eq!(get_token_balance(&mut banks_client, &foreign_token_account.pubkey()).await, 0);
+}
+
+#[tokio::test]
+async fn test_trade_account_substitutions() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent {
+        pricing_mode: state::PricingMode::Lmsr,
+        liquidity: 100,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // payer holds YES and NO tokens, holder's YES tokens aren't theirs to sell
+    let yes_token_account = Keypair::new();
+    let no_token_account = Keypair::new();
+    let holder_token_account = Keypair::new();
+    let accounts = [
+        (&yes_token_account, 0, payer.pubkey()),
+        (&no_token_account, 1, payer.pubkey()),
+        (&holder_token_account, 0, Pubkey::new_unique()),
+    ];
+    for (token_account, side_index, owner) in accounts {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &event.outcome_mints[side_index],
+            &owner,
+        ).await.unwrap();
+    }
+    event.mint_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &[yes_token_account.pubkey(), no_token_account.pubkey()],
+        10
+    ).await.unwrap();
+
+    let sell_shares = || instruction::sell_shares(
+        &id(),
+        &payer.pubkey(),
+        &event.authority,
+        &event.event_account,
+        &event.outcome_mints[0],
+        &yes_token_account.pubkey(),
+        &spl_token::id(),
+        &event.fee_vault(),
+        None,
+        instruction::SellSharesArgs {
+            side_index: 0,
+            num_tokens: 5,
+            min_proceeds: 0
+        },
+    ).unwrap();
+    let substitutions = [
+        (3, event.outcome_mints[1], error::PredictChainError::InvalidTokenMint),
+        (4, no_token_account.pubkey(), error::PredictChainError::InvalidTokenMint),
+        (4, holder_token_account.pubkey(), error::PredictChainError::WrongOwner),
+        (5, system_program::id(), error::PredictChainError::InvalidTokenProgram),
+        (6, event.collateral_vault, error::PredictChainError::WrongAccountAddress),
+    ];
+    for (index, substitute, expected) in substitutions {
+        let mut instruction = sell_shares();
+        instruction.accounts[index].pubkey = substitute;
+        let result = process_instruction(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            instruction
+        ).await;
+        assert_program_error(result, expected);
+    }
+
+    // complete sets need the event mints and matching token accounts, in order
+    let complete_set = |merge: bool| {
+        let mints = event.outcome_mints.clone();
+        let token_accounts = [yes_token_account.pubkey(), no_token_account.pubkey()];
+        if merge {
+            instruction::merge_complete_set(
+                &id(),
+                &payer.pubkey(),
+                &event.authority,
+                &event.event_account,
+                &mints,
+                &token_accounts,
+                &spl_token::id(),
+                None,
+                instruction::MergeCompleteSetArgs { num_sets: 1 },
+            ).unwrap()
+        } else {
+            instruction::mint_complete_set(
+                &id(),
+                &payer.pubkey(),
+                &event.authority,
+                &event.event_account,
+                &mints,
+                &token_accounts,
+                &spl_token::id(),
+                None,
+                instruction::MintCompleteSetArgs { num_sets: 1 },
+            ).unwrap()
+        }
+    };
+    for merge in [false, true] {
+        // accounts after the system program shift by one when minting
+        let offset = if merge { 4 } else { 5 };
+        let token_program = offset - 1;
+        let substitutions = [
+            (offset, event.outcome_mints[1], error::PredictChainError::InvalidTokenMint),
+            (offset + 2, no_token_account.pubkey(), error::PredictChainError::InvalidTokenMint),
+            (token_program, system_program::id(), error::PredictChainError::InvalidTokenProgram),
+        ];
+        for (index, substitute, expected) in substitutions {
+            let mut instruction = complete_set(merge);
+            instruction.accounts[index].pubkey = substitute;
+            let result = process_instruction(
+                &mut banks_client,
+                &payer,
+                &recent_blockhash,
+                instruction
+            ).await;
+            assert_program_error(result, expected);
+        }
+    }
+
+    // redemption checks the token program too
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await.unwrap();
+    let mut redeem_shares = instruction::redeem_shares(
+        &id(),
+        &payer.pubkey(),
+        &event.authority,
+        &event.event_account,
+        &event.outcome_mints[0],
+        &yes_token_account.pubkey(),
+        &spl_token::id(),
+        None,
+        instruction::RedeemSharesArgs { num_tokens: 5 },
+    ).unwrap();
+    redeem_shares.accounts[5].pubkey = system_program::id();
+    let result = process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        redeem_shares
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidTokenProgram);
+}
+
+#[tokio::test]
+async fn test_resolve_event() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent::new();
+
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // only the stored resolve authority can resolve
+    let impostor = Keypair::new();
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &impostor,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolveAuthority);
+
+    // side index must be in [0, 1]
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        2
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidSide);
+
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        1
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.winning_side, 1);
+    assert!(event_data.resolved_at > 0);
+
+    // resolution is final
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::DecisionAlreadyMade);
+
+    // no more shares can be purchased
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        1,
+        u64::MAX
+    ).await;
+    assert_program_error(result, error::PredictChainError::EventClosed);
+}
+
+#[tokio::test]
+async fn test_transfer_resolve_authority() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent::new();
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let new_resolve_authority = Keypair::new();
+    let impostor = Keypair::new();
+
+    // nothing to accept before a proposal
+    let result = event.accept_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &Keypair::new()
+    ).await;
+    assert_program_error(result, error::PredictChainError::NoPendingResolveAuthority);
+
+    // only the resolve authority proposes
+    let result = event.propose_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &impostor,
+        &impostor.pubkey()
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolveAuthority);
+
+    event.propose_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &new_resolve_authority.pubkey()
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.resolve_authority, payer.pubkey());
+    assert_eq!(event_data.pending_resolve_authority, new_resolve_authority.pubkey());
+
+    // only the proposed account accepts
+    let result = event.accept_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &impostor
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolveAuthority);
+
+    // the current authority still resolves until the proposal is accepted
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &new_resolve_authority,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolveAuthority);
+
+    event.accept_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &new_resolve_authority
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.resolve_authority, new_resolve_authority.pubkey());
+    assert_eq!(event_data.pending_resolve_authority, Pubkey::default());
+
+    // the previous authority lost its rights
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        1
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolveAuthority);
+
+    // a proposal can be cancelled by proposing the default key
+    event.propose_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &new_resolve_authority,
+        &impostor.pubkey()
+    ).await.unwrap();
+    event.propose_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &new_resolve_authority,
+        &Pubkey::default()
+    ).await.unwrap();
+    let result = event.accept_resolve_authority(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &Keypair::new()
+    ).await;
+    assert_program_error(result, error::PredictChainError::NoPendingResolveAuthority);
+
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &new_resolve_authority,
+        1
+    ).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_resolver_set() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let resolvers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let resolver_keys = resolvers.iter().map(|resolver| resolver.pubkey()).collect::<Vec<_>>();
+
+    // the threshold must be reachable by distinct resolvers
+    for (resolvers, resolution_threshold) in [
+        (resolver_keys.clone(), 0),
+        (resolver_keys.clone(), 4),
+        (vec![resolver_keys[0], resolver_keys[1], resolver_keys[0]], 2),
+    ] {
+        let result = TestEvent {
+            resolvers,
+            resolution_threshold,
+            ..TestEvent::new()
+        }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+        assert_program_error(result, error::PredictChainError::InvalidResolvers);
+    }
+
+    // resolvers only vote once trading has closed
+    let open_event = TestEvent {
+        resolvers: resolver_keys.clone(),
+        resolution_threshold: 2,
+        ..TestEvent::new()
+    };
+    open_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let result = open_event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[0],
+        state::Resolution::Side(0)
+    ).await;
+    assert_program_error(result, error::PredictChainError::TradingNotClosed);
+
+    let event = TestEvent {
+        resolvers: resolver_keys.clone(),
+        resolution_threshold: 2,
+        close_time: 1,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.resolution, event.resolution());
+
+    // the resolve authority can't decide alone
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::ResolverVoteRequired);
+    let result = event.void_event(&mut banks_client, &payer, &recent_blockhash, &payer).await;
+    assert_program_error(result, error::PredictChainError::ResolverVoteRequired);
+
+    let result = event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &Keypair::new(),
+        state::Resolution::Side(0)
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolver);
+
+    // votes must fit the event
+    let result = event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[0],
+        state::Resolution::Side(2)
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidSide);
+    let result = event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[0],
+        state::Resolution::Value(1)
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongEventType);
+
+    // conflicting votes are recorded and settle nothing
+    event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[0],
+        state::Resolution::Side(0)
+    ).await.unwrap();
+    event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[1],
+        state::Resolution::Side(1)
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Open);
+    let resolution_account = banks_client.get_account(event.resolution()).await.unwrap().unwrap();
+    let resolution_data = state::ResolutionAccount::decode(&resolution_account.data).unwrap();
+    assert_eq!(resolution_data.event, event.event_account);
+    assert_eq!(resolution_data.threshold, 2);
+    assert_eq!(
+        resolution_data.votes.iter().map(|vote| vote.vote).collect::<Vec<_>>(),
+        vec![Some(state::Resolution::Side(0)), Some(state::Resolution::Side(1)), None]
+    );
+
+    // a resolver may change its vote, the threshold settles the event
+    event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[1],
+        state::Resolution::Side(0)
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.winning_side, 0);
+
+    let result = event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[2],
+        state::Resolution::Side(1)
+    ).await;
+    assert_program_error(result, error::PredictChainError::DecisionAlreadyMade);
+
+    // scalar events settle on the value voted by the threshold, values out of
+    // bounds matching the bound they settle on
+    let scalar_event = TestEvent {
+        event_type: state::EventType::Scalar,
+        outcome_labels: vec!["LONG".to_string(), "SHORT".to_string()],
+        lower_bound: 0,
+        upper_bound: 100,
+        resolvers: resolver_keys[..2].to_vec(),
+        resolution_threshold: 2,
+        close_time: 1,
+        ..TestEvent::new()
+    };
+    scalar_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    scalar_event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[0],
+        state::Resolution::Value(150)
+    ).await.unwrap();
+
+    let resolution_account = banks_client.get_account(scalar_event.resolution()).await.unwrap().unwrap();
+    let resolution_data = state::ResolutionAccount::decode(&resolution_account.data).unwrap();
+    assert_eq!(resolution_data.votes[0].vote, Some(state::Resolution::Value(100)));
+
+    scalar_event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[1],
+        state::Resolution::Value(200)
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(scalar_event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.resolved_value, 100);
+
+    // a resolution account only counts votes for its own event
+    let other_event = TestEvent {
+        resolvers: resolver_keys[..2].to_vec(),
+        resolution_threshold: 2,
+        close_time: 1,
+        ..TestEvent::new()
+    };
+    other_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let mut resolution_account = banks_client.get_account(other_event.resolution()).await.unwrap().unwrap();
+    resolution_account.data = banks_client.get_account(scalar_event.resolution()).await.unwrap().unwrap().data;
+    context.set_account(&other_event.resolution(), &resolution_account.into());
+    let result = other_event.vote_resolution(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &resolvers[0],
+        state::Resolution::Side(0)
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongAccountAddress);
+}
+
+#[tokio::test]
+async fn test_optimistic_resolution() {
+    let mut context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let recent_blockhash = context.last_blockhash;
+    let bond = 1_000_000;
+    let balance = 1_000_000_000;
+
+    // proposals need a bond and exclude a resolver set
+    for (dispute_window, proposal_bond, resolvers, error) in [
+        (-1, bond, vec![], error::PredictChainError::InvalidProposal),
+        (100, 0, vec![], error::PredictChainError::InvalidAmount),
+        (100, bond, vec![payer.pubkey()], error::PredictChainError::InvalidProposal),
+    ] {
+        let result = TestEvent {
+            dispute_window,
+            proposal_bond,
+            resolution_threshold: 1,
+            resolvers,
+            ..TestEvent::new()
+        }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+        assert_program_error(result, error);
+    }
+
+    let proposer = Keypair::new();
+    let disputer = Keypair::new();
+    for account in [&proposer, &disputer] {
+        create_account(
+            &mut context.banks_client,
+            &payer,
+            &recent_blockhash,
+            account,
+            balance,
+            0,
+            &system_program::id()
+        ).await.unwrap();
+    }
+
+    let event = TestEvent {
+        dispute_window: 100,
+        proposal_bond: bond,
+        close_time: 1,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let disputed_event = TestEvent {
+        dispute_window: i64::MAX,
+        proposal_bond: bond,
+        close_time: 1,
+        ..TestEvent::new()
+    };
+    disputed_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let scalar_event = TestEvent {
+        event_type: state::EventType::Scalar,
+        outcome_labels: vec!["LONG".to_string(), "SHORT".to_string()],
+        lower_bound: 0,
+        upper_bound: 100,
+        dispute_window: i64::MAX,
+        proposal_bond: bond,
+        close_time: 1,
+        ..TestEvent::new()
+    };
+    scalar_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let event_lamports = context.banks_client.get_balance(event.event_account).await.unwrap();
+
+    // the resolve authority only decides disputes
+    let result = event.resolve_event(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProposalRequired);
+
+    let result = disputed_event.finalize_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer.pubkey()
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongProposalStatus);
+
+    // proposals are only taken once trading has closed
+    let open_event = TestEvent {
+        dispute_window: 100,
+        proposal_bond: bond,
+        ..TestEvent::new()
+    };
+    open_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let result = open_event.propose_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer,
+        state::Resolution::Side(1)
+    ).await;
+    assert_program_error(result, error::PredictChainError::TradingNotClosed);
+
+    // the resolve authority can void an event until a resolution is proposed
+    open_event.void_event(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer
+    ).await.unwrap();
+    let event_account = context.banks_client.get_account(open_event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Voided);
+
+    let result = event.propose_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer,
+        state::Resolution::Void
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidProposal);
+    event.propose_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer,
+        state::Resolution::Side(1)
+    ).await.unwrap();
+
+    let event_account = context.banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_account.lamports, event_lamports + bond);
+    assert_eq!(event_data.proposal.status, state::ProposalStatus::Proposed);
+    assert_eq!(event_data.proposal.proposer, proposer.pubkey());
+    assert_eq!(event_data.proposal.winning_side, 1);
+
+    let result = event.void_event(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProposalRequired);
+    assert_eq!(context.banks_client.get_balance(proposer.pubkey()).await.unwrap(), balance - bond);
+
+    let result = event.propose_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &disputer,
+        state::Resolution::Side(0)
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongProposalStatus);
+    let result = event.finalize_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer.pubkey()
+    ).await;
+    assert_program_error(result, error::PredictChainError::DisputeWindowOpen);
+
+    // the undisputed proposal settles the event once the window has ended
+    let recent_blockhash = advance_clock(&mut context, 100).await;
+    let result = event.dispute_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &disputer
+    ).await;
+    assert_program_error(result, error::PredictChainError::DisputeWindowClosed);
+    let result = event.finalize_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &disputer.pubkey()
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongAccountAddress);
+    event.finalize_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer.pubkey()
+    ).await.unwrap();
+
+    let event_account = context.banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_account.lamports, event_lamports);
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.winning_side, 1);
+    assert_eq!(context.banks_client.get_balance(proposer.pubkey()).await.unwrap(), balance);
+
+    // a dispute escalates to the resolve authority, the wrong party losing its bond
+    disputed_event.propose_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer,
+        state::Resolution::Side(0)
+    ).await.unwrap();
+
+    // disputes are taken while the program is paused, the window running on
+    initialize_config(&mut context.banks_client, &payer, &recent_blockhash, 0, &payer.pubkey()).await.unwrap();
+    set_paused(&mut context.banks_client, &payer, &recent_blockhash, &payer, true).await.unwrap();
+    disputed_event.dispute_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &disputer
+    ).await.unwrap();
+    let result = disputed_event.settle_dispute(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &proposer.pubkey(),
+        &disputer.pubkey(),
+        state::Resolution::Side(1)
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    set_paused(&mut context.banks_client, &payer, &recent_blockhash, &payer, false).await.unwrap();
+    let recent_blockhash = advance_clock(&mut context, 1).await;
+
+    let result = disputed_event.finalize_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer.pubkey()
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongProposalStatus);
+    let result = disputed_event.settle_dispute(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer,
+        &proposer.pubkey(),
+        &disputer.pubkey(),
+        state::Resolution::Side(0)
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolveAuthority);
+    disputed_event.settle_dispute(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &proposer.pubkey(),
+        &disputer.pubkey(),
+        state::Resolution::Side(1)
+    ).await.unwrap();
+
+    let event_account = context.banks_client.get_account(disputed_event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_account.lamports, event_lamports);
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.winning_side, 1);
+    assert_eq!(event_data.proposal.status, state::ProposalStatus::Disputed);
+    assert_eq!(event_data.proposal.disputer, disputer.pubkey());
+    assert_eq!(context.banks_client.get_balance(proposer.pubkey()).await.unwrap(), balance - bond);
+    assert_eq!(context.banks_client.get_balance(disputer.pubkey()).await.unwrap(), balance + bond);
+
+    // proposed values are compared once clamped to the event bounds
+    scalar_event.propose_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &proposer,
+        state::Resolution::Value(150)
+    ).await.unwrap();
+    scalar_event.dispute_resolution(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &disputer
+    ).await.unwrap();
+    scalar_event.settle_dispute(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &proposer.pubkey(),
+        &disputer.pubkey(),
+        state::Resolution::Value(100)
+    ).await.unwrap();
+
+    let event_account = context.banks_client.get_account(scalar_event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.resolved_value, 100);
+    assert_eq!(context.banks_client.get_balance(proposer.pubkey()).await.unwrap(), balance);
+    assert_eq!(context.banks_client.get_balance(disputer.pubkey()).await.unwrap(), balance);
+}
+
+#[tokio::test]
+async fn test_resolve_from_oracle() {
+    let mut context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let recent_blockhash = context.last_blockhash;
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let close_time = now + 100;
+
+    let oracle = Pubkey::new_unique();
+    let oracle_event = |strike| TestEvent {
+        oracle,
+        strike,
+        price_expo: -3,
+        max_staleness: 60,
+        max_confidence_bps: 10,
+        close_time,
+        ..TestEvent::new()
+    };
+
+    // oracle events need an oracle program in the config
+    let result = oracle_event(0).try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidOracle);
+    initialize_config(&mut context.banks_client, &payer, &recent_blockhash, 0, &payer.pubkey()).await.unwrap();
+
+    // oracle events resolve themselves, binary ones against the strike
+    let result = TestEvent {
+        dispute_window: 100,
+        proposal_bond: 1,
+        ..oracle_event(0)
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidOracle);
+    let result = TestEvent {
+        outcome_labels: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+        ..oracle_event(0)
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidOutcomes);
+
+    let event = oracle_event(100_000);
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let below_strike_event = oracle_event(110_000);
+    below_strike_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let scalar_event = TestEvent {
+        event_type: state::EventType::Scalar,
+        outcome_labels: vec!["LONG".to_string(), "SHORT".to_string()],
+        lower_bound: 0,
+        upper_bound: 200_000,
+        ..oracle_event(0)
+    };
+    scalar_event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    context.set_account(
+        &oracle,
+        &price_account(105_000, 50, -3, oracle::STATUS_TRADING, now).into()
+    );
+
+    let result = event.resolve_from_oracle(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &oracle
+    ).await;
+    assert_program_error(result, error::PredictChainError::TradingNotClosed);
+    let result = event.resolve_event(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::OracleRequired);
+
+    // the price must be published after the close time
+    let recent_blockhash = advance_clock(&mut context, 100).await;
+    let result = event.resolve_from_oracle(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &oracle
+    ).await;
+    assert_program_error(result, error::PredictChainError::StalePrice);
+
+    // and be trading with a narrow confidence interval in the event exponent
+    for (conf, expo, status, error) in [
+        (50, -3, 0, error::PredictChainError::UncertainPrice),
+        (200, -3, oracle::STATUS_TRADING, error::PredictChainError::UncertainPrice),
+        (50, -2, oracle::STATUS_TRADING, error::PredictChainError::InvalidOracle),
+    ] {
+        context.set_account(
+            &oracle,
+            &price_account(105_000, conf, expo, status, close_time).into()
+        );
+        let recent_blockhash = advance_clock(&mut context, 1).await;
+        let result = event.resolve_from_oracle(
+            &mut context.banks_client,
+            &payer,
+            &recent_blockhash,
+            &oracle
+        ).await;
+        assert_program_error(result, error);
+    }
+
+    // at most max_staleness seconds before the resolution
+    context.set_account(
+        &oracle,
+        &price_account(105_000, 50, -3, oracle::STATUS_TRADING, close_time).into()
+    );
+    let recent_blockhash = advance_clock(&mut context, 100).await;
+    let result = event.resolve_from_oracle(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &oracle
+    ).await;
+    assert_program_error(result, error::PredictChainError::StalePrice);
+
+    // price accounts must belong to the oracle program
+    let mut foreign_price_account = price_account(105_000, 50, -3, oracle::STATUS_TRADING, close_time);
+    foreign_price_account.owner = Pubkey::new_unique();
+    context.set_account(&oracle, &foreign_price_account.into());
+    let result = below_strike_event.resolve_from_oracle(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &oracle
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidOracle);
+
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    context.set_account(
+        &oracle,
+        &price_account(105_000, 50, -3, oracle::STATUS_TRADING, now).into()
+    );
+    let recent_blockhash = advance_clock(&mut context, 1).await;
+    let result = event.resolve_from_oracle(
+        &mut context.banks_client,
+        &payer,
+        &recent_blockhash,
+        &Pubkey::new_unique()
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongAccountAddress);
+
+    for oracle_event in [&event, &below_strike_event, &scalar_event] {
+        oracle_event.resolve_from_oracle(
+            &mut context.banks_client,
+            &payer,
+            &recent_blockhash,
+            &oracle
+        ).await.unwrap();
+    }
+
+    let event_account = context.banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.winning_side, 0);
+    let event_account = context.banks_client.get_account(below_strike_event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.winning_side, 1);
+    let event_account = context.banks_client.get_account(scalar_event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.resolved_value, 105_000);
+}
+
+#[tokio::test]
+async fn test_sell_shares() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    for pricing_mode in [
+        state::PricingMode::FixedPrice,
+        state::PricingMode::ConstantProduct,
+        state::PricingMode::Lmsr,
+    ] {
+        let event = TestEvent {
+            pricing_mode,
+            liquidity: 100,
+            ..TestEvent::new()
+        };
+        event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+        // holder buys 10 NO tokens
+        let holder = Keypair::new();
+        let token_account = Keypair::new();
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &token_account,
+            &event.outcome_mints[1],
+            &holder.pubkey(),
+        ).await.unwrap();
+        let event_balance_before = banks_client.get_balance(event.event_account).await.unwrap();
+        event.purchase_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            &token_account,
+            1,
+            10
+        ).await;
+        let cost = banks_client.get_balance(event.event_account).await.unwrap() - event_balance_before;
+
+        // selling everything back returns the cost, minus rounding
+        let proceeds = match pricing_mode {
+            state::PricingMode::FixedPrice => event.price_per_share * 10,
+            state::PricingMode::ConstantProduct => {
+                let reserve = event.price_per_share * 100;
+                let amount = event.price_per_share * 10;
+                pricing::constant_product_sell_proceeds(reserve + cost - amount, reserve + cost, amount).unwrap()
+            }
+            state::PricingMode::Lmsr => {
+                pricing::lmsr_sell_proceeds(100, &[0, 10], 1, 10, event.price_per_share).unwrap()
+            }
+        };
+        assert!(proceeds <= cost && cost - proceeds <= 2);
+
+        // trade is rejected when proceeds are below min_proceeds
+        let result = event.sell_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &holder,
+            &token_account.pubkey(),
+            1,
+            10,
+            proceeds + 1
+        ).await;
+        assert_program_error(result, error::PredictChainError::SlippageExceeded);
+
+        // only tokens of the sold side are accepted
+        let result = event.sell_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &holder,
+            &token_account.pubkey(),
+            0,
+            10,
+            0
+        ).await;
+        assert_program_error(result, error::PredictChainError::InvalidTokenMint);
+
+        event.sell_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &holder,
+            &token_account.pubkey(),
+            1,
+            10,
+            proceeds
+        ).await.unwrap();
+        assert_eq!(banks_client.get_balance(holder.pubkey()).await.unwrap(), proceeds);
+        assert_eq!(get_token_balance(&mut banks_client, &token_account.pubkey()).await, 0);
+
+        let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+        assert_eq!(event_account.lamports, event_balance_before + cost - proceeds);
+        let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+        assert_eq!(event_data.volume, cost - proceeds);
+    }
+}
+
+#[tokio::test]
+async fn test_sell_shares_after_resolution() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent::new();
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    event.purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10
+    ).await;
+
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        1
+    ).await.unwrap();
+
+    let result = event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account.pubkey(),
+        0,
+        10,
+        0
+    ).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_trading_window() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    // the window can't be empty
+    let result = TestEvent {
+        open_time: 100,
+        close_time: 100,
+        ..TestEvent::new()
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidBounds);
+
+    // trading hasn't opened yet
+    let event = TestEvent {
+        open_time: i64::MAX - 1,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10,
+        u64::MAX
+    ).await;
+    assert_program_error(result, error::PredictChainError::OutsideTradingWindow);
+
+    // trading has closed, shares obtained from a complete set can't be sold
+    let event = TestEvent {
+        pricing_mode: state::PricingMode::Lmsr,
+        liquidity: 100,
+        close_time: 1,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let yes_token_account = Keypair::new();
+    let no_token_account = Keypair::new();
+    for (token_account, mint) in [&yes_token_account, &no_token_account].iter().zip(&event.outcome_mints) {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            mint,
+            &payer.pubkey(),
+        ).await.unwrap();
+    }
+    event.mint_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &[yes_token_account.pubkey(), no_token_account.pubkey()],
+        5
+    ).await.unwrap();
+
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &yes_token_account,
+        0,
+        10,
+        u64::MAX
+    ).await;
+    assert_program_error(result, error::PredictChainError::OutsideTradingWindow);
+
+    let result = event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &yes_token_account.pubkey(),
+        0,
+        5,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::OutsideTradingWindow);
+}
+
+#[tokio::test]
+async fn test_complete_sets() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent {
+        pricing_mode: state::PricingMode::Lmsr,
+        liquidity: 100,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let holder = Keypair::new();
+    let yes_token_account = Keypair::new();
+    let no_token_account = Keypair::new();
+    for (token_account, mint) in [&yes_token_account, &no_token_account].iter().zip(&event.outcome_mints) {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            mint,
+            &holder.pubkey(),
+        ).await.unwrap();
+    }
+
+    // deposit collateral for 5 pairs
+    event.mint_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &[yes_token_account.pubkey(), no_token_account.pubkey()],
+        5
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &yes_token_account.pubkey()).await, 5);
+    assert_eq!(get_token_balance(&mut banks_client, &no_token_account.pubkey()).await, 5);
+
+    // market maker state is untouched
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, event.price_per_share * 5);
+    assert_eq!(event_data.quantities(), vec![0, 0]);
+
+    // a pair can't be merged from a single side
+    let result = event.merge_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &[yes_token_account.pubkey(), yes_token_account.pubkey()],
+        3
+    ).await;
+    assert!(result.is_err());
+
+    // burn 3 pairs for their collateral
+    event.merge_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &[yes_token_account.pubkey(), no_token_account.pubkey()],
+        3
+    ).await.unwrap();
+    assert_eq!(banks_client.get_balance(holder.pubkey()).await.unwrap(), event.price_per_share * 3);
+    assert_eq!(get_token_balance(&mut banks_client, &yes_token_account.pubkey()).await, 2);
+    assert_eq!(get_token_balance(&mut banks_client, &no_token_account.pubkey()).await, 2);
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, event.price_per_share * 2);
+
+    // can't merge more pairs than held
+    let result = event.merge_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &[yes_token_account.pubkey(), no_token_account.pubkey()],
+        4
+    ).await;
+    assert!(result.is_err());
+
+    // fixed price events don't support complete sets
+    let fixed_event = TestEvent::new();
+    fixed_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let yes_token_account = Keypair::new();
+    let no_token_account = Keypair::new();
+    for (token_account, mint) in [&yes_token_account, &no_token_account].iter().zip(&fixed_event.outcome_mints) {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            mint,
+            &holder.pubkey(),
+        ).await.unwrap();
+    }
+    let result = fixed_event.mint_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &[yes_token_account.pubkey(), no_token_account.pubkey()],
+        1
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidPricingMode);
+    let result = fixed_event.merge_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &[yes_token_account.pubkey(), no_token_account.pubkey()],
+        1
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidPricingMode);
+}
+
+#[tokio::test]
+async fn test_redeem_shares() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent::new();
+
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // holder buys 17 YES and 10 NO tokens
+    let holder = Keypair::new();
+    let yes_token_account = Keypair::new();
+    let no_token_account = Keypair::new();
+    let purchases = [
+        (&yes_token_account, &event.outcome_mints[0], 0, 17),
+        (&no_token_account, &event.outcome_mints[1], 1, 10),
+    ];
+    for (token_account, mint, side_index, num_tokens) in purchases {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            mint,
+            &holder.pubkey(),
+        ).await.unwrap();
+        event.purchase_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            token_account,
+            side_index,
+            num_tokens
+        ).await;
+    }
+
+    // can't redeem before resolution
+    let result = event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &yes_token_account.pubkey(),
+        &event.outcome_mints[0],
+        17
+    ).await;
+    assert_program_error(result, error::PredictChainError::NoDecisionMadeYet);
+
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await.unwrap();
+
+    // losing side can't be redeemed
+    let result = event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &no_token_account.pubkey(),
+        &event.outcome_mints[1],
+        10
+    ).await;
+    assert_program_error(result, error::PredictChainError::NotRedeemable);
+
+    // winners split the whole pool pro-rata
+    let event_balance_before = banks_client.get_account(event.event_account).await.unwrap().unwrap().lamports;
+    event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &yes_token_account.pubkey(),
+        &event.outcome_mints[0],
+        7
+    ).await.unwrap();
+    let pool = event.price_per_share * 27;
+    let first_payout = pool * 7 / 17;
+    assert_eq!(banks_client.get_balance(holder.pubkey()).await.unwrap(), first_payout);
+    assert_eq!(get_token_balance(&mut banks_client, &yes_token_account.pubkey()).await, 10);
+
+    event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &yes_token_account.pubkey(),
+        &event.outcome_mints[0],
+        10
+    ).await.unwrap();
+    assert_eq!(banks_client.get_balance(holder.pubkey()).await.unwrap(), pool);
+    assert_eq!(get_token_balance(&mut banks_client, &yes_token_account.pubkey()).await, 0);
+
+    // event account keeps its rent exemption
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    assert_eq!(event_account.lamports, event_balance_before - pool);
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 0);
+}
+
+#[tokio::test]
+async fn test_unheld_winning_outcome() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    // holder buys 17 A and 10 B tokens, nobody holds C
+    let event = TestEvent::with_outcomes(&["A", "B", "C"]);
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let holder = Keypair::new();
+    let token_accounts = [Keypair::new(), Keypair::new()];
+    for (side_index, (token_account, num_tokens)) in token_accounts.iter().zip([17, 10]).enumerate() {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &event.outcome_mints[side_index],
+            &holder.pubkey(),
+        ).await.unwrap();
+        event.purchase_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            token_account,
+            side_index as u8,
+            num_tokens
+        ).await;
+    }
+
+    // the pool of the unheld winning outcome is refunded to the other holders
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        2
+    ).await.unwrap();
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.winning_side, 2);
+    let payout_pools: Vec<_> = event_data.outcomes.iter().map(|outcome| outcome.payout_pool).collect();
+    assert_eq!(payout_pools, [event.price_per_share * 17, event.price_per_share * 10, 0]);
+
+    for (side_index, (token_account, num_tokens)) in token_accounts.iter().zip([17, 10]).enumerate() {
+        event.redeem_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &holder,
+            &token_account.pubkey(),
+            &event.outcome_mints[side_index],
+            num_tokens
+        ).await.unwrap();
+    }
+    assert_eq!(banks_client.get_balance(holder.pubkey()).await.unwrap(), event.price_per_share * 27);
+
+    // the same goes for a scalar side nobody holds
+    let scalar_event = TestEvent {
+        event_type: state::EventType::Scalar,
+        outcome_labels: vec!["LONG".to_string(), "SHORT".to_string()],
+        lower_bound: 0,
+        upper_bound: 100,
+        ..TestEvent::new()
+    };
+    scalar_event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let long_holder = Keypair::new();
+    let long_token_account = Keypair::new();
+    create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &long_token_account,
+        &scalar_event.outcome_mints[0],
+        &long_holder.pubkey(),
+    ).await.unwrap();
+    scalar_event.purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &long_token_account,
+        0,
+        5
+    ).await;
+    scalar_event.resolve_scalar_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        50
+    ).await.unwrap();
+    scalar_event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &long_holder,
+        &long_token_account.pubkey(),
+        &scalar_event.outcome_mints[0],
+        5
+    ).await.unwrap();
+    assert_eq!(
+        banks_client.get_balance(long_holder.pubkey()).await.unwrap(),
+        scalar_event.price_per_share * 5
+    );
+}
+
+#[tokio::test]
+async fn test_void_event() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    let event = TestEvent::new();
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // holder buys 10 YES and 30 NO tokens, then sells 10 NO back
+    let holder = Keypair::new();
+    let yes_token_account = Keypair::new();
+    let no_token_account = Keypair::new();
+    let purchases = [(&yes_token_account, 0, 10), (&no_token_account, 1, 30)];
+    for (token_account, side_index, num_tokens) in purchases {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &event.outcome_mints[side_index as usize],
+            &holder.pubkey(),
+        ).await.unwrap();
+        event.purchase_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            token_account,
+            side_index,
+            num_tokens
+        ).await;
+    }
+    event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &no_token_account.pubkey(),
+        1,
+        10,
+        0
+    ).await.unwrap();
+    let holder_balance = banks_client.get_balance(holder.pubkey()).await.unwrap();
+
+    // only the resolve authority can void the event
+    let not_authority = Keypair::new();
+    let result = event.void_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &not_authority
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongResolveAuthority);
+
+    event.void_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Voided);
+    assert_eq!(event_data.outcomes[0].payout_pool, event.price_per_share * 10);
+    assert_eq!(event_data.outcomes[1].payout_pool, event.price_per_share * 20);
+
+    // a voided event can't be resolved or traded
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::DecisionAlreadyMade);
+    let result = event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &yes_token_account.pubkey(),
+        0,
+        10,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::EventClosed);
+
+    // shares can't be redeemed as if the event were resolved
+    let result = event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &yes_token_account.pubkey(),
+        &event.outcome_mints[0],
+        10
+    ).await;
+    assert_program_error(result, error::PredictChainError::NoDecisionMadeYet);
+
+    // holders of either side get back what they paid
+    event.claim_refund(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &yes_token_account.pubkey(),
+        &event.outcome_mints[0],
+        10
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &yes_token_account.pubkey()).await, 0);
+    assert_eq!(
+        banks_client.get_balance(holder.pubkey()).await.unwrap(),
+        holder_balance + event.price_per_share * 10
+    );
+
+    event.claim_refund(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &no_token_account.pubkey(),
+        &event.outcome_mints[1],
+        20
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &no_token_account.pubkey()).await, 0);
+    assert_eq!(
+        banks_client.get_balance(holder.pubkey()).await.unwrap(),
+        holder_balance + event.price_per_share * 30
+    );
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 0);
+}
+
+#[tokio::test]
+async fn test_categorical_event() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    // constant-product market maker only prices binary events
+    let result = TestEvent {
+        pricing_mode: state::PricingMode::ConstantProduct,
+        liquidity: 100,
+        ..TestEvent::with_outcomes(&["Home", "Draw", "Away"])
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidOutcomes);
+
+    // LMSR events are capped to keep trades within the compute budget
+    let result = TestEvent {
+        pricing_mode: state::PricingMode::Lmsr,
+        liquidity: 100,
+        ..TestEvent::with_outcomes(&["A", "B", "C", "D", "E"])
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidOutcomes);
+
+    let event = TestEvent {
+        pricing_mode: state::PricingMode::Lmsr,
+        liquidity: 100,
+        ..TestEvent::with_outcomes(&["Home", "Draw", "Away"])
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    let labels: Vec<&str> = event_data.outcomes.iter().map(|outcome| outcome.label.as_str()).collect();
+    assert_eq!(labels, ["Home", "Draw", "Away"]);
+    let mints: Vec<Pubkey> = event_data.outcomes.iter().map(|outcome| outcome.mint_address).collect();
+    assert_eq!(mints, event.outcome_mints);
+
+    let token_accounts = [Keypair::new(), Keypair::new(), Keypair::new()];
+    for (token_account, mint) in token_accounts.iter().zip(&event.outcome_mints) {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            mint,
+            &payer.pubkey(),
+        ).await.unwrap();
+    }
+
+    // outcome index must be in range
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::purchase_shares(
+            &id(),
+            &payer.pubkey(),
+            &event.authority,
+            &event.event_account,
+            &event.outcome_mints[2],
+            &token_accounts[2].pubkey(),
+            &spl_token::id(),
+            &event.fee_vault(),
+            None,
+            instruction::PurchaseSharesArgs {
+                side_index: 3,
+                num_tokens: 10,
+                max_cost: u64::MAX
+            },
+        ).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_err());
+
+    // buying the last outcome is priced over all three
+    let cost = pricing::lmsr_buy_cost(100, &[0, 0, 0], 2, 10, event.price_per_share).unwrap();
+    assert!(cost < event.price_per_share * 10 / 2);
+    let event_balance_before = banks_client.get_balance(event.event_account).await.unwrap();
+    event.purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &token_accounts[2],
+        2,
+        10
+    ).await;
+    assert_eq!(
+        banks_client.get_balance(event.event_account).await.unwrap(),
+        event_balance_before + cost
+    );
+
+    // a complete set holds one token of each of the three outcomes
+    let token_account_addresses: Vec<Pubkey> = token_accounts.iter().map(|account| account.pubkey()).collect();
+    event.mint_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &token_account_addresses,
+        4
+    ).await.unwrap();
+    for (token_account, balance) in token_accounts.iter().zip([4, 4, 14]) {
+        assert_eq!(get_token_balance(&mut banks_client, &token_account.pubkey()).await, balance);
+    }
+
+    event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &token_accounts[2].pubkey(),
+        2,
+        5,
+        0
+    ).await.unwrap();
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.quantities(), vec![0, 0, 5]);
+
+    // winning outcome must be in range
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        3
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidSide);
+
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        2
+    ).await.unwrap();
+
+    // holders of the last outcome take the whole pool
+    event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &token_accounts[2].pubkey(),
+        &event.outcome_mints[2],
+        9
+    ).await.unwrap();
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let rent = banks_client.get_rent().await.unwrap();
+    assert_eq!(event_account.lamports, rent.minimum_balance(event_account.data.len()));
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 0);
+}
+
+#[tokio::test]
+async fn test_scalar_event() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    // bounds must enclose a non-empty range
+    let result = TestEvent {
+        event_type: state::EventType::Scalar,
+        lower_bound: 200,
+        upper_bound: 200,
+        ..TestEvent::with_outcomes(&["LONG", "SHORT"])
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidBounds);
+
+    // scalar events have exactly a LONG and a SHORT outcome
+    let result = TestEvent {
+        event_type: state::EventType::Scalar,
+        lower_bound: 100,
+        upper_bound: 200,
+        ..TestEvent::with_outcomes(&["LONG", "SHORT", "FLAT"])
+    }.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidOutcomes);
+
+    let event = TestEvent {
+        event_type: state::EventType::Scalar,
+        lower_bound: 100,
+        upper_bound: 200,
+        ..TestEvent::with_outcomes(&["LONG", "SHORT"])
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // holder buys 10 LONG and 30 SHORT tokens
+    let holder = Keypair::new();
+    let long_token_account = Keypair::new();
+    let short_token_account = Keypair::new();
+    let purchases = [(&long_token_account, 0, 10), (&short_token_account, 1, 30)];
+    for (token_account, side_index, num_tokens) in purchases {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &event.outcome_mints[side_index as usize],
+            &holder.pubkey(),
+        ).await.unwrap();
+        event.purchase_shares(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &payer,
+            token_account,
+            side_index,
+            num_tokens
+        ).await;
+    }
+    let pool = event.price_per_share * 40;
+
+    // scalar events can't pick a single winner
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongEventType);
+
+    // a value three quarters up the range gives three quarters of the pool to LONG
+    event.resolve_scalar_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        175
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.status, state::EventStatus::Resolved);
+    assert_eq!(event_data.resolved_value, 175);
+    assert_eq!(event_data.outcomes[0].payout_pool, pool * 3 / 4);
+    assert_eq!(event_data.outcomes[1].payout_pool, pool / 4);
+
+    event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &long_token_account.pubkey(),
+        &event.outcome_mints[0],
+        10
+    ).await.unwrap();
+    assert_eq!(banks_client.get_balance(holder.pubkey()).await.unwrap(), pool * 3 / 4);
+
+    event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &holder,
+        &short_token_account.pubkey(),
+        &event.outcome_mints[1],
+        30
+    ).await.unwrap();
+    assert_eq!(banks_client.get_balance(holder.pubkey()).await.unwrap(), pool);
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 0);
+
+    // values outside the bounds are clamped
+    let event = TestEvent {
+        event_type: state::EventType::Scalar,
+        lower_bound: -50,
+        upper_bound: 50,
+        ..TestEvent::with_outcomes(&["LONG", "SHORT"])
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &token_account,
+        1
+    ).await;
+    event.purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &token_account,
+        1,
+        4
+    ).await;
+    event.resolve_scalar_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        -1_000
+    ).await.unwrap();
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.resolved_value, -50);
+    assert_eq!(event_data.outcomes[0].payout_pool, 0);
+    assert_eq!(event_data.outcomes[1].payout_pool, event.price_per_share * 4);
+}
+
+#[tokio::test]
+async fn test_spl_collateral() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let collateral_mint = Keypair::new();
+    create_mint(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &collateral_mint,
+        rent.minimum_balance(spl_token::state::Mint::LEN),
+        &payer.pubkey(),
+    ).await.unwrap();
+
+    let event = TestEvent {
+        price_per_share: 1_000,
+        collateral_mint: Some(collateral_mint.pubkey()),
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // collateral is held in a vault owned by the event authority
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.collateral_mint, collateral_mint.pubkey());
+    assert_eq!(event_data.collateral_vault, event.collateral_vault);
+    let vault_account = banks_client.get_account(event.collateral_vault).await.unwrap().unwrap();
+    let vault_data = spl_token::state::Account::unpack(&vault_account.data).unwrap();
+    assert_eq!(vault_data.mint, collateral_mint.pubkey());
+    assert_eq!(vault_data.owner, event.authority);
+
+    // holder is funded with 100_000 collateral tokens
+    let holder = Keypair::new();
+    let collateral_account = Keypair::new();
+    let yes_token_account = Keypair::new();
+    for (token_account, mint) in [
+        (&collateral_account, collateral_mint.pubkey()),
+        (&yes_token_account, event.outcome_mints[0]),
+    ] {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &mint,
+            &holder.pubkey(),
+        ).await.unwrap();
+    }
+    let mut transaction = Transaction::new_with_payer(
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &collateral_mint.pubkey(),
+            &collateral_account.pubkey(),
+            &payer.pubkey(),
+            &[],
+            100_000,
+        ).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // purchase pulls exactly the cost from the holder's collateral account
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::purchase_shares(
+            &id(),
+            &holder.pubkey(),
+            &event.authority,
+            &event.event_account,
+            &event.outcome_mints[0],
+            &yes_token_account.pubkey(),
+            &spl_token::id(),
+            &event.fee_vault(),
+            Some((&event.collateral_vault, &collateral_account.pubkey())),
+            instruction::PurchaseSharesArgs {
+                side_index: 0,
+                num_tokens: 17,
+                max_cost: u64::MAX
+            },
+        ).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &holder], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &yes_token_account.pubkey()).await, 17);
+    assert_eq!(get_token_balance(&mut banks_client, &collateral_account.pubkey()).await, 83_000);
+    assert_eq!(get_token_balance(&mut banks_client, &event.collateral_vault).await, 17_000);
+
+    // selling and redeeming pay out of the vault
+    let sell_and_redeem = [
+        instruction::sell_shares(
+            &id(),
+            &holder.pubkey(),
+            &event.authority,
+            &event.event_account,
+            &event.outcome_mints[0],
+            &yes_token_account.pubkey(),
+            &spl_token::id(),
+            &event.fee_vault(),
+            Some((&event.collateral_vault, &collateral_account.pubkey())),
+            instruction::SellSharesArgs {
+                side_index: 0,
+                num_tokens: 5,
+                min_proceeds: 5_000
+            },
+        ).unwrap(),
+        instruction::resolve_event(
+            &id(),
+            &payer.pubkey(),
+            &event.event_account,
+            instruction::ResolveEventArgs {
+                winning_side: 0
+            },
+        ).unwrap(),
+        instruction::redeem_shares(
+            &id(),
+            &holder.pubkey(),
+            &event.authority,
+            &event.event_account,
+            &event.outcome_mints[0],
+            &yes_token_account.pubkey(),
+            &spl_token::id(),
+            Some((&event.collateral_vault, &collateral_account.pubkey())),
+            instruction::RedeemSharesArgs {
+                num_tokens: 12
+            },
+        ).unwrap(),
+    ];
+
+    // payout requires the collateral accounts
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            spl_token::instruction::approve(
+                &spl_token::id(),
+                &yes_token_account.pubkey(),
+                &event.authority,
+                &holder.pubkey(),
+                &[],
+                5,
+            ).unwrap(),
+            instruction::sell_shares(
+                &id(),
+                &holder.pubkey(),
+                &event.authority,
+                &event.event_account,
+                &event.outcome_mints[0],
+                &yes_token_account.pubkey(),
+                &spl_token::id(),
+                &event.fee_vault(),
+                None,
+                instruction::SellSharesArgs {
+                    side_index: 0,
+                    num_tokens: 5,
+                    min_proceeds: 0
+                },
+            ).unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &holder], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_err());
+
+    let mut instructions = vec![spl_token::instruction::approve(
+        &spl_token::id(),
+        &yes_token_account.pubkey(),
+        &event.authority,
+        &holder.pubkey(),
+        &[],
+        17,
+    ).unwrap()];
+    instructions.extend(sell_and_redeem);
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &holder], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &yes_token_account.pubkey()).await, 0);
+    assert_eq!(get_token_balance(&mut banks_client, &collateral_account.pubkey()).await, 100_000);
+    assert_eq!(get_token_balance(&mut banks_client, &event.collateral_vault).await, 0);
+
+    // no lamports moved through the event account
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    assert_eq!(event_account.lamports, rent.minimum_balance(event_account.data.len()));
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 0);
+}
+
+#[tokio::test]
+async fn test_trading_fees() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    // the protocol fee rate of new events is set in the config
+    let protocol_fee_recipient = Keypair::new();
+    initialize_config(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        100,
+        &protocol_fee_recipient.pubkey()
+    ).await.unwrap();
+
+    // fee rates can't exceed the maximum
+    let event = TestEvent {
+        creator_fee_bps: 950,
+        ..TestEvent::new()
+    };
+    let result = event.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::InvalidFee);
+
+    let event = TestEvent {
+        creator_fee_bps: 200,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let fee_vault_rent = rent.minimum_balance(0);
+    assert_eq!(banks_client.get_balance(event.fee_vault()).await.unwrap(), fee_vault_rent);
+
+    // fees are paid on top of the cost of a purchase
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10,
+        10_000_000
+    ).await;
+    assert_program_error(result, error::PredictChainError::SlippageExceeded);
+    event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10,
+        10_300_000
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 10_000_000);
+    assert_eq!(event_data.protocol_fees, 100_000);
+    assert_eq!(event_data.creator_fees, 200_000);
+    assert_eq!(
+        banks_client.get_balance(event.fee_vault()).await.unwrap(),
+        fee_vault_rent + 300_000
+    );
+
+    // and deducted from the proceeds of a sale
+    let result = event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account.pubkey(),
+        0,
+        5,
+        5_000_000
+    ).await;
+    assert_program_error(result, error::PredictChainError::SlippageExceeded);
+    event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account.pubkey(),
+        0,
+        5,
+        4_850_000
+    ).await.unwrap();
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 5_000_000);
+    assert_eq!(event_data.protocol_fees, 150_000);
+    assert_eq!(event_data.creator_fees, 300_000);
+    assert_eq!(
+        event_account.lamports,
+        rent.minimum_balance(event_account.data.len()) + 5_000_000
+    );
+    assert_eq!(
+        banks_client.get_balance(event.fee_vault()).await.unwrap(),
+        fee_vault_rent + 450_000
+    );
+
+    // only the fee recipients can withdraw, each its own fees
+    let result = event.withdraw_fees(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        None
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongFeeRecipient);
+
+    for (recipient, fees) in [(&protocol_fee_recipient, 150_000), (&event.creator, 300_000)] {
+        let balance = banks_client.get_balance(recipient.pubkey()).await.unwrap();
+        event.withdraw_fees(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            recipient,
+            None
+        ).await.unwrap();
+        assert_eq!(banks_client.get_balance(recipient.pubkey()).await.unwrap(), balance + fees);
+    }
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.protocol_fees, 0);
+    assert_eq!(event_data.creator_fees, 0);
+    assert_eq!(event_data.volume, 5_000_000);
+    assert_eq!(banks_client.get_balance(event.fee_vault()).await.unwrap(), fee_vault_rent);
+}
+
+#[tokio::test]
+async fn test_event_account_substitutions() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+
+    // fees of an event are kept in its fee vault
+    let event = TestEvent {
+        creator_fee_bps: 200,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10,
+        10_200_000
+    ).await.unwrap();
+    let fee_vault_balance = banks_client.get_balance(event.fee_vault()).await.unwrap();
+
+    // the attacker creates an event with a resolution account
+    let attacker_event = TestEvent {
+        resolvers: vec![payer.pubkey()],
+        resolution_threshold: 1,
+        ..TestEvent::new()
+    };
+    attacker_event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // other accounts of the program, including one holding a forged event
+    // naming the attacker as creator of the event, can't pass for an event
+    let mut forged_event = state::EventAccount::try_from_slice(
+        &banks_client.get_account(event.event_account).await.unwrap().unwrap().data,
+    ).unwrap();
+    forged_event.creator = attacker_event.creator.pubkey();
+    forged_event.creator_fees = fee_vault_balance;
+    let forged_event_account = Pubkey::new_unique();
+    let data = forged_event.try_to_vec().unwrap();
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: id(),
+        ..Account::default()
+    };
+    context.set_account(&forged_event_account, &account.into());
+
+    for event_account in [
+        attacker_event.resolution(),
+        attacker_event.metadata_account,
+        forged_event_account,
+    ] {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::withdraw_fees(
+                &id(),
+                &attacker_event.creator.pubkey(),
+                &address::find_authority_address(&id(), &event_account).0,
+                &event_account,
+                &event.fee_vault(),
+                &spl_token::id(),
+                None,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer, &attacker_event.creator], recent_blockhash);
+        let result = banks_client.process_transaction(transaction).await;
+        if event_account == forged_event_account {
+            assert_program_error(result, error::PredictChainError::WrongAccountAddress);
+        } else {
+            assert!(result.is_err());
+        }
+    }
+
+    // nor can the fee vault of another event be withdrawn from
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::withdraw_fees(
+            &id(),
+            &attacker_event.creator.pubkey(),
+            &attacker_event.authority,
+            &attacker_event.event_account,
+            &event.fee_vault(),
+            &spl_token::id(),
+            None,
+        ).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &attacker_event.creator], recent_blockhash);
+    assert_program_error(
+        banks_client.process_transaction(transaction).await,
+        error::PredictChainError::WrongAccountAddress,
+    );
+    assert_eq!(banks_client.get_balance(event.fee_vault()).await.unwrap(), fee_vault_balance);
+}
+
+#[tokio::test]
+async fn test_trading_fees_spl_collateral() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let collateral_mint = Keypair::new();
+    create_mint(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &collateral_mint,
+        rent.minimum_balance(spl_token::state::Mint::LEN),
+        &payer.pubkey(),
+    ).await.unwrap();
+
+    initialize_config(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        50,
+        &payer.pubkey()
+    ).await.unwrap();
+    let event = TestEvent {
+        price_per_share: 1_000,
+        collateral_mint: Some(collateral_mint.pubkey()),
+        creator_fee_bps: 150,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // fees are held in a token account owned by the event authority
+    let fee_vault_account = banks_client.get_account(event.fee_vault()).await.unwrap().unwrap();
+    let fee_vault_data = spl_token::state::Account::unpack(&fee_vault_account.data).unwrap();
+    assert_eq!(fee_vault_data.mint, collateral_mint.pubkey());
+    assert_eq!(fee_vault_data.owner, event.authority);
+
+    let collateral_account = Keypair::new();
+    let creator_collateral_account = Keypair::new();
+    let user_token_account = Keypair::new();
+    for (token_account, mint, owner) in [
+        (&collateral_account, collateral_mint.pubkey(), payer.pubkey()),
+        (&creator_collateral_account, collateral_mint.pubkey(), event.creator.pubkey()),
+        (&user_token_account, event.outcome_mints[0], payer.pubkey()),
+    ] {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &mint,
+            &owner,
+        ).await.unwrap();
+    }
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &collateral_mint.pubkey(),
+            &collateral_account.pubkey(),
+            &payer.pubkey(),
+            &[],
+            1_000_000,
+        ).unwrap()
+    ).await.unwrap();
+
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        instruction::purchase_shares(
+            &id(),
+            &payer.pubkey(),
+            &event.authority,
+            &event.event_account,
+            &event.outcome_mints[0],
+            &user_token_account.pubkey(),
+            &spl_token::id(),
+            &event.fee_vault(),
+            Some((&event.collateral_vault, &collateral_account.pubkey())),
+            instruction::PurchaseSharesArgs {
+                side_index: 0,
+                num_tokens: 100,
+                max_cost: u64::MAX
+            },
+        ).unwrap()
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &event.collateral_vault).await, 100_000);
+    assert_eq!(get_token_balance(&mut banks_client, &event.fee_vault()).await, 2_000);
+    assert_eq!(get_token_balance(&mut banks_client, &collateral_account.pubkey()).await, 898_000);
+
+    // token fees need a collateral token account to be paid into
+    let result = event.withdraw_fees(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &event.creator,
+        None
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidCollateralAccount);
+    event.withdraw_fees(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &event.creator,
+        Some(&creator_collateral_account.pubkey())
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &creator_collateral_account.pubkey()).await, 1_500);
+    assert_eq!(get_token_balance(&mut banks_client, &event.fee_vault()).await, 500);
+
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.volume, 100_000);
+    assert_eq!(event_data.protocol_fees, 500);
+    assert_eq!(event_data.creator_fees, 0);
+}
+
+#[tokio::test]
+async fn test_config() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let (config, bump_seed) = address::find_config_address(&id());
+
+    let protocol_fee_recipient = Pubkey::new_unique();
+
+    // only the upgrade authority of the program sets the first admin
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::initialize_config(
+            &id(),
+            &payer.pubkey(),
+            instruction::ConfigArgs {
+                admin: payer.pubkey(),
+                protocol_fee_bps: 100,
+                protocol_fee_recipient,
+                oracle_program: oracle_program()
+            },
+        ).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    let result = banks_client.process_transaction(transaction).await;
+    assert_program_error(result, error::PredictChainError::WrongUpgradeAuthority);
+
+    initialize_config(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        100,
+        &protocol_fee_recipient
+    ).await.unwrap();
+    let result = initialize_config(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        200,
+        &protocol_fee_recipient
+    ).await;
+    assert_program_error(result, error::PredictChainError::AlreadyInUse);
+
+    let config_account = banks_client.get_account(config).await.unwrap().unwrap();
+    let config_data = state::Config::try_from_slice(&config_account.data).unwrap();
+    assert_eq!(config_data, state::Config {
+        bump_seed,
+        admin: payer.pubkey(),
+        protocol_fee_bps: 100,
+        protocol_fee_recipient,
+        paused: false,
+        oracle_program: oracle_program(),
+    });
+
+    // only the admin updates the config, within the fee limits
+    let new_admin = Keypair::new();
+    let update_config = |admin: &Keypair, protocol_fee_bps| {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::update_config(
+                &id(),
+                &admin.pubkey(),
+                instruction::ConfigArgs {
+                    admin: new_admin.pubkey(),
+                    protocol_fee_bps,
+                    protocol_fee_recipient: new_admin.pubkey(),
+                    oracle_program: Pubkey::default()
+                },
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer, admin], recent_blockhash);
+        transaction
+    };
+    let result = banks_client.process_transaction(update_config(&new_admin, 300)).await;
+    assert_program_error(result, error::PredictChainError::WrongAdmin);
+    let result = banks_client.process_transaction(update_config(&payer, 1_001)).await;
+    assert_program_error(result, error::PredictChainError::InvalidFee);
+    banks_client.process_transaction(update_config(&payer, 300)).await.unwrap();
+
+    let config_account = banks_client.get_account(config).await.unwrap().unwrap();
+    let config_data = state::Config::try_from_slice(&config_account.data).unwrap();
+    assert_eq!(config_data.admin, new_admin.pubkey());
+    assert_eq!(config_data.protocol_fee_bps, 300);
+    assert_eq!(config_data.protocol_fee_recipient, new_admin.pubkey());
+    assert_eq!(config_data.oracle_program, Pubkey::default());
+
+    // new events take the protocol fee settings of the config
+    let event = TestEvent::new();
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let event_account = banks_client.get_account(event.event_account).await.unwrap().unwrap();
+    let event_data = state::EventAccount::try_from_slice(&event_account.data).unwrap();
+    assert_eq!(event_data.protocol_fee_bps, 300);
+    assert_eq!(event_data.protocol_fee_recipient, new_admin.pubkey());
+
+    // the previous admin can't pause the program anymore
+    let result = set_paused(&mut banks_client, &payer, &recent_blockhash, &payer, true).await;
+    assert_program_error(result, error::PredictChainError::WrongAdmin);
+}
+
+#[tokio::test]
+async fn test_paused() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    initialize_config(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        0,
+        &payer.pubkey()
+    ).await.unwrap();
+
+    let event = TestEvent {
+        pricing_mode: state::PricingMode::Lmsr,
+        liquidity: 100,
+        creator_fee_bps: 100,
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+    event.purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        10
+    ).await;
+
+    // the config account can't be substituted to dodge the pause
+    let mut purchase_shares = instruction::purchase_shares(
+        &id(),
+        &payer.pubkey(),
+        &event.authority,
+        &event.event_account,
+        &event.outcome_mints[0],
+        &user_token_account.pubkey(),
+        &spl_token::id(),
+        &event.fee_vault(),
+        None,
+        instruction::PurchaseSharesArgs {
+            side_index: 0,
+            num_tokens: 1,
+            max_cost: u64::MAX
+        },
+    ).unwrap();
+    purchase_shares.accounts[8].pubkey = Pubkey::new_unique();
+    let result = process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        purchase_shares
+    ).await;
+    assert_program_error(result, error::PredictChainError::WrongAccountAddress);
+
+    let stranger = Keypair::new();
+    let result = set_paused(&mut banks_client, &payer, &recent_blockhash, &stranger, true).await;
+    assert_program_error(result, error::PredictChainError::WrongAdmin);
+    set_paused(&mut banks_client, &payer, &recent_blockhash, &payer, true).await.unwrap();
+
+    // every state-changing instruction fails while paused
+    let paused_event = TestEvent::new();
+    let result = paused_event.try_init_event(&mut context, &payer, &recent_blockhash).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    let result = event.try_purchase_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account,
+        0,
+        11,
+        u64::MAX
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    let result = event.sell_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account.pubkey(),
+        0,
+        5,
+        0
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    let result = event.mint_complete_set(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &[user_token_account.pubkey(), Pubkey::new_unique()],
+        1
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    let result = event.set_event_metadata(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &event.creator,
+        instruction::EventMetadataArgs::default()
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    let result = event.withdraw_fees(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &event.creator,
+        None
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    let result = event.void_event(&mut banks_client, &payer, &recent_blockhash, &payer).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+    let result = event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        1
+    ).await;
+    assert_program_error(result, error::PredictChainError::ProgramPaused);
+
+    // and succeeds again once unpaused
+    set_paused(&mut banks_client, &payer, &recent_blockhash, &payer, false).await.unwrap();
+    event.resolve_event(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        0
+    ).await.unwrap();
+    event.redeem_shares(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &payer,
+        &user_token_account.pubkey(),
+        &event.outcome_mints[0],
+        10
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &user_token_account.pubkey()).await, 0);
+}
+
+#[tokio::test]
+async fn test_collateral_account_substitutions() {
+    let (mut context, mut banks_client, payer, recent_blockhash) = start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let collateral_mint = Keypair::new();
+    let other_mint = Keypair::new();
+    for mint in [&collateral_mint, &other_mint] {
+        create_mint(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            mint,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            &payer.pubkey(),
+        ).await.unwrap();
+    }
+
+    let event = TestEvent {
+        price_per_share: 1_000,
+        collateral_mint: Some(collateral_mint.pubkey()),
+        ..TestEvent::new()
+    };
+    event.init_event(&mut context, &payer, &recent_blockhash).await;
+
+    // payer holds collateral in one account, and other tokens in another
+    let collateral_account = Keypair::new();
+    let decoy_vault = Keypair::new();
+    let other_account = Keypair::new();
+    let accounts = [
+        (&collateral_account, collateral_mint.pubkey()),
+        (&decoy_vault, collateral_mint.pubkey()),
+        (&other_account, other_mint.pubkey()),
+    ];
+    for (token_account, mint) in accounts {
+        create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            token_account,
+            &mint,
+            &payer.pubkey(),
+        ).await.unwrap();
+    }
+    for (mint, token_account) in [(&collateral_mint, &collateral_account), (&other_mint, &other_account)] {
+        let mut transaction = Transaction::new_with_payer(
+            &[spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &token_account.pubkey(),
+                &payer.pubkey(),
+                &[],
+                100_000,
+            ).unwrap()],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+
+    let user_token_account = Keypair::new();
+    event.prepare_accounts_for_purchase(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &user_token_account,
+        0
+    ).await;
+
+    let purchase_shares = || instruction::purchase_shares(
+        &id(),
+        &payer.pubkey(),
+        &event.authority,
+        &event.event_account,
+        &event.outcome_mints[0],
+        &user_token_account.pubkey(),
+        &spl_token::id(),
+        &event.fee_vault(),
+        Some((&event.collateral_vault, &collateral_account.pubkey())),
+        instruction::PurchaseSharesArgs {
+            side_index: 0,
+            num_tokens: 10,
+            max_cost: u64::MAX
+        },
+    ).unwrap();
+    for (index, substitute) in [(9, decoy_vault.pubkey()), (10, other_account.pubkey())] {
+        let mut instruction = purchase_shares();
+        instruction.accounts[index].pubkey = substitute;
+        let result = process_instruction(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            instruction
+        ).await;
+        assert_program_error(result, error::PredictChainError::InvalidCollateralAccount);
+    }
+
+    // collateral accounts can't be left out
+    let mut instruction = purchase_shares();
+    instruction.accounts.truncate(9);
+    let result = process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        instruction
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidCollateralAccount);
+
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        purchase_shares()
+    ).await.unwrap();
+    assert_eq!(get_token_balance(&mut banks_client, &event.collateral_vault).await, 10_000);
+    assert_eq!(get_token_balance(&mut banks_client, &decoy_vault.pubkey()).await, 0);
+
+    // proceeds are only paid into an account of the collateral mint
+    process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        spl_token::instruction::approve(
+            &spl_token::id(),
+            &user_token_account.pubkey(),
+            &event.authority,
+            &payer.pubkey(),
+            &[],
+            10,
+        ).unwrap()
+    ).await.unwrap();
+    let mut sell_shares = instruction::sell_shares(
+        &id(),
+        &payer.pubkey(),
+        &event.authority,
+        &event.event_account,
+        &event.outcome_mints[0],
+        &user_token_account.pubkey(),
+        &spl_token::id(),
+        &event.fee_vault(),
+        Some((&event.collateral_vault, &collateral_account.pubkey())),
+        instruction::SellSharesArgs {
+            side_index: 0,
+            num_tokens: 10,
+            min_proceeds: 0
+        },
+    ).unwrap();
+    sell_shares.accounts[9].pubkey = other_account.pubkey();
+    let result = process_instruction(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        sell_shares
+    ).await;
+    assert_program_error(result, error::PredictChainError::InvalidCollateralAccount);
 }
 
 